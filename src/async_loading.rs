@@ -0,0 +1,165 @@
+use crate::i18n;
+use crate::loading::load_with_layers;
+use crate::merge::Layer;
+use crate::models::{ConfigError, LoadingParam, OwnedLoadingParam};
+use crate::report::LoadReport;
+use crate::telemetry::CallerSpan;
+use crate::values::json_to_value;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Instant;
+use tokio::process::Command;
+
+/// Future returned by `AsyncSource::load`
+pub type SourceFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<JsonValue, Box<dyn Error + Send + Sync>>> + Send + 'a>>;
+
+/// A configuration source loaded without blocking the runtime, e.g. over HTTP
+pub trait AsyncSource: Send + Sync {
+    /// Gets the name of the source in the load report, logs and errors
+    fn name(&self) -> String;
+
+    /// Loads the values of the source as a JSON object
+    fn load(&self) -> SourceFuture<'_>;
+}
+
+/// Runs a command and reads its standard output as a JSON object
+///
+/// e.g. `CommandSource::new("vault", "vault", &["kv", "get", "-format=json", "secret/app"])`
+pub struct CommandSource {
+    name: String,
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandSource {
+    /// Creates a source that runs the program with the given arguments
+    pub fn new(name: &str, program: &str, args: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+}
+
+impl AsyncSource for CommandSource {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn load(&self) -> SourceFuture<'_> {
+        Box::pin(async move {
+            let output = Command::new(&self.program)
+                .args(&self.args)
+                .kill_on_drop(true)
+                .output()
+                .await?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("{} ({})", output.status, stderr.trim()).into());
+            }
+            Ok(serde_json::from_slice(&output.stdout)?)
+        })
+    }
+}
+
+/// Loads configuration like `load_config_with_param`, without blocking the runtime
+///
+/// The file and environment variables are read on a blocking thread of the tokio runtime.
+pub async fn load_config_with_param_async<T>(param: &LoadingParam<'_>) -> Result<T, ConfigError>
+where
+    T: for<'de> Deserialize<'de> + Serialize + Send + 'static,
+{
+    load_async(param, &[], false)
+        .await
+        .map(|(config, _)| config)
+}
+
+/// Loads configuration from the file, the async sources and the env prefixes
+///
+/// Async sources are listed by priority, like env prefixes: values from earlier sources
+/// override those from later ones. They override the file and are overridden by the env
+/// prefixes. The sources are awaited concurrently; a failure or a value that is not a JSON
+/// object is reported as `ConfigError::SourceFailed`, for the first failing source by
+/// priority. The report lists the sources as `SourceKind::Async`.
+pub async fn load_config_with_sources_async<T>(
+    param: &LoadingParam<'_>,
+    sources: &[&dyn AsyncSource],
+) -> Result<(T, LoadReport), ConfigError>
+where
+    T: for<'de> Deserialize<'de> + Serialize + Send + 'static,
+{
+    load_async(param, sources, true).await
+}
+
+async fn load_async<T>(
+    param: &LoadingParam<'_>,
+    sources: &[&dyn AsyncSource],
+    report_ignored_keys: bool,
+) -> Result<(T, LoadReport), ConfigError>
+where
+    T: for<'de> Deserialize<'de> + Serialize + Send + 'static,
+{
+    let started = Instant::now();
+    let locale = i18n::resolve(param.locale);
+
+    // Load the async sources concurrently, in the span of the caller; the results keep the
+    // order of the sources
+    let caller = CallerSpan::current();
+    let results = join_all(sources.iter().map(|source| {
+        caller.instrument(async move {
+            let name = source.name();
+            log::info!("Loading configuration from source: '{}'", name);
+            (name, source.load().await)
+        })
+    }))
+    .await;
+
+    let mut layers = Vec::new();
+    for (name, result) in results {
+        let values = match result {
+            Ok(JsonValue::Object(values)) => values,
+            Ok(_) => {
+                return Err(ConfigError::SourceFailed {
+                    name,
+                    reason: "the source did not return a JSON object".to_string(),
+                    locale,
+                })
+            }
+            Err(err) => {
+                return Err(ConfigError::SourceFailed {
+                    name,
+                    reason: err.to_string(),
+                    locale,
+                })
+            }
+        };
+        let values = values
+            .into_iter()
+            .map(|(key, value)| (key, json_to_value(value, &name)))
+            .collect();
+        layers.push(Layer::new(name, values));
+    }
+    // Layers are merged lowest priority first
+    layers.reverse();
+
+    // Run the blocking part of the pipeline in the span of the caller, with parameters the
+    // blocking thread owns
+    let param = OwnedLoadingParam::new(param);
+    let task = tokio::task::spawn_blocking(move || {
+        caller.in_scope(|| load_with_layers(&param.param(), layers, report_ignored_keys, started))
+    });
+    match task.await {
+        Ok(result) => result,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => {
+            log::debug!("Configuration loading was cancelled: {}", err);
+            Err(ConfigError::LoadCancelled { locale })
+        }
+    }
+}
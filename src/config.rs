@@ -1,19 +1,87 @@
+use crate::format::{Documents, Format};
+use crate::i18n::{Locale, Warning};
+use crate::merge;
 use serde::Deserialize;
-use serde_yaml;
 use std::env;
-use std::fs::File;
-use std::io::Read;
 use std::path::Path;
 
+/// Environment variable naming the active environment, also used as the active profile
+const ENV_VAR: &str = "ENV";
+
+/// Gets the active profile from the `ENV` environment variable, if set and not empty
+pub(crate) fn active_profile() -> Option<String> {
+    env::var(ENV_VAR).ok().filter(|env| !env.is_empty())
+}
+
+/// Loads configuration from a file.
+///
+/// Files are parsed as YAML, which also covers plain JSON. In a multi-document YAML
+/// file, the untagged documents are merged with those whose `profile:` matches `ENV`,
+/// following their `_merge` directives. When the corresponding
+/// cargo features are enabled, `.json5` and `.ron` files are parsed in their own format.
+/// Without the `yaml` feature, files are parsed according to their extension.
 pub fn load_config_from_file<T, P>(path: P) -> Option<T>
 where
     T: for<'de> Deserialize<'de>,
     P: AsRef<std::path::Path>,
 {
-    let mut file = File::open(path).ok()?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).ok()?;
-    serde_yaml::from_str(&contents).ok()
+    let path = path.as_ref();
+    match legacy_format(path) {
+        Some(format) => {
+            let origin = path.display().to_string();
+            let documents = format
+                .parse_file(path, &origin, active_profile().as_deref())
+                .ok()?;
+            deserialize_documents(documents, &origin)
+        }
+        None => parse_yaml(path),
+    }
+}
+
+/// Merges the documents of a file like layers and deserializes the result
+fn deserialize_documents<T>(documents: Documents, origin: &String) -> Option<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let values = merge::merge_documents(documents, origin).ok()?;
+    config::Value::new(Some(origin), config::ValueKind::Table(values))
+        .try_deserialize()
+        .ok()
+}
+
+/// Gets the format of the file when it is not read as YAML
+fn legacy_format(path: &Path) -> Option<Format> {
+    let format = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(Format::from_extension);
+    match format {
+        Some(Format::Json5) | Some(Format::Ron) => format,
+        _ if !cfg!(feature = "yaml") => format,
+        _ => None,
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn parse_yaml<T>(path: &Path) -> Option<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let contents = std::fs::read_to_string(path).ok()?;
+    let origin = path.display().to_string();
+    let documents = crate::yaml::parse(&contents, &origin, active_profile().as_deref())
+        .map_err(|err| Warning::FileParseFailed { path, error: &err }.log(Locale::from_env()))
+        .ok()?;
+    deserialize_documents(documents, &origin)
+}
+
+#[cfg(not(feature = "yaml"))]
+fn parse_yaml<T>(path: &Path) -> Option<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    Warning::YamlNotEnabled { path }.log(Locale::from_env());
+    None
 }
 
 /// Loads configuration from environment-specific or default YAML files.
@@ -25,7 +93,8 @@ where
 /// 4. `config.yaml`
 ///
 /// Where `ENV` is the value of the environment variable "ENV". If "ENV" is not set,
-/// it defaults to searching `config.yml` and `config.yaml`.
+/// only `config.yml` and `config.yaml` are searched. Without an environment-specific
+/// file, the documents of `config.yml` tagged with `profile: {ENV}` are applied.
 ///
 /// # Returns
 /// `Some(T)` if a valid configuration file is found and parsed successfully,
@@ -54,17 +123,14 @@ pub fn load_config<T>() -> Option<T>
 where
     T: for<'de> Deserialize<'de>,
 {
-    let env_var = env::var("ENV").ok();
-
-    let candidates = match env_var.as_deref() {
-        Some(env) if !env.is_empty() => {
-            vec![
-                format!("config.{}.yml", env),
-                format!("config.{}.yaml", env),
-            ]
-        }
-        _ => vec!["config.yml".to_string(), "config.yaml".to_string()],
+    let mut candidates = match active_profile() {
+        Some(env) => vec![
+            format!("config.{}.yml", env),
+            format!("config.{}.yaml", env),
+        ],
+        None => vec![],
     };
+    candidates.extend(["config.yml".to_string(), "config.yaml".to_string()]);
 
     for file_name in candidates {
         if let Some(config) = load_config_from_file::<T, _>(&file_name) {
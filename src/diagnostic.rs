@@ -0,0 +1,461 @@
+use crate::format::Format;
+use crate::i18n::{self, Locale};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where and why a configuration file or value failed to load
+///
+/// Carried by `ConfigError::Parse` and `ConfigError::Deserialize`. Line and column are
+/// 1-based; each field is `None` when the underlying parser does not report it.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub message: String,          // Message of the underlying parser or deserializer
+    pub origin: Option<String>,   // File path or environment variable the value comes from
+    pub path: Option<PathBuf>,    // Configuration file, when the origin is a file
+    pub line: Option<usize>,      // Line of the error in the file
+    pub column: Option<usize>,    // Column of the error in the file
+    pub snippet: Option<String>,  // Source line with a caret under the column
+    pub key_path: Option<String>, // Dotted key path of the value, e.g. `servers[0].port`
+    pub expected: Option<String>, // Type expected by the target struct
+    pub found: Option<String>,    // Value found instead
+    pub missing: bool,            // Whether the key is required but not set
+    pub(crate) source_text: Option<String>,
+    pub(crate) locale: Locale, // Locale of the load that raised it, used by `Display`
+    cause: Box<dyn Error + Send + Sync>,
+}
+
+impl Diagnostic {
+    /// Describes a file that failed to parse, locating the error in its content when possible
+    pub(crate) fn parse(
+        origin: &str,
+        content: Option<&str>,
+        cause: Box<dyn Error + Send + Sync>,
+    ) -> Self {
+        let message = cause.to_string();
+        let (line, column) = locate(&message);
+        let mut diagnostic = Diagnostic {
+            message,
+            origin: Some(origin.to_string()),
+            path: Some(PathBuf::from(origin)),
+            line,
+            column,
+            snippet: None,
+            key_path: None,
+            expected: None,
+            found: None,
+            missing: false,
+            source_text: content.map(str::to_string),
+            locale: Locale::from_env(),
+            cause,
+        };
+        diagnostic.render_snippet();
+        diagnostic
+    }
+
+    /// Describes a value that failed to deserialize into the target type
+    ///
+    /// Hands back errors that are not about a value, e.g. a frozen configuration.
+    /// When the value comes from `file`, the key is located in the file content; the
+    /// location is left out when the key cannot be told apart from other occurrences.
+    pub(crate) fn deserialize(
+        err: config::ConfigError,
+        file: Option<SourceFile>,
+    ) -> Result<Self, config::ConfigError> {
+        let Description {
+            origin,
+            key,
+            expected,
+            found,
+            missing,
+        } = match describe(&err) {
+            Some(description) => description,
+            None => return Err(err),
+        };
+
+        let file = file.filter(|file| origin.as_deref() == Some(&*file.path.display().to_string()));
+        let key_path = key.map(|key| normalize_key_path(&key));
+        let mut diagnostic = Diagnostic {
+            message: err.to_string(),
+            origin,
+            path: file.as_ref().map(|file| file.path.to_path_buf()),
+            line: None,
+            column: None,
+            snippet: None,
+            key_path,
+            expected,
+            found,
+            missing,
+            source_text: file
+                .as_ref()
+                .and_then(|file| fs::read_to_string(file.path).ok()),
+            locale: Locale::from_env(),
+            cause: Box::new(err),
+        };
+
+        let located = match (&file, &diagnostic.source_text, &diagnostic.key_path) {
+            (Some(file), Some(content), Some(key_path)) => {
+                file.format
+                    .locate(content, &key_steps(key_path), file.profile)
+            }
+            _ => None,
+        };
+        if let Some((line, column)) = located {
+            diagnostic.line = Some(line);
+            diagnostic.column = Some(column);
+        }
+        diagnostic.render_snippet();
+        Ok(diagnostic)
+    }
+
+    /// Gets the underlying parser or deserializer error
+    pub fn cause(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.cause
+    }
+
+    /// Gets the byte offset of the line and column in the source text
+    pub fn offset(&self) -> Option<usize> {
+        let content = self.source_text.as_deref()?;
+        let line = self.line?;
+        let line_start: usize = content
+            .split_inclusive('\n')
+            .take(line - 1)
+            .map(str::len)
+            .sum();
+        let line_text = source_line(content, line)?;
+        let column = self.column.unwrap_or(1).saturating_sub(1);
+        let column_offset: usize = line_text.chars().take(column).map(char::len_utf8).sum();
+        Some(line_start + column_offset)
+    }
+
+    fn render_snippet(&mut self) {
+        let (Some(content), Some(line)) = (&self.source_text, self.line) else {
+            return;
+        };
+        let Some(text) = source_line(content, line) else {
+            return;
+        };
+
+        let number = line.to_string();
+        let gutter = " ".repeat(number.len());
+        let mut snippet = format!("{} |\n{} | {}", gutter, number, text);
+        if let Some(column) = self.column {
+            let padding: String = text
+                .chars()
+                .take(column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            snippet.push_str(&format!("\n{} | {}^", gutter, padding));
+        }
+        self.snippet = Some(snippet);
+    }
+
+    /// Describes where the error is, e.g. `configuration file "app.yaml" at line 3, column 9`
+    pub fn location_in(&self, locale: Locale) -> String {
+        i18n::location(self, locale)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location_in(self.locale), self.message)?;
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{}", snippet)?;
+        }
+        Ok(())
+    }
+}
+
+/// The configuration file a deserialization error is located in
+pub(crate) struct SourceFile<'a> {
+    pub(crate) path: &'a Path,
+    pub(crate) format: Format,
+    pub(crate) profile: Option<&'a str>, // Selects the documents of a multi-document YAML file
+}
+
+/// A step of a key path: a table key or an array index
+#[derive(Debug, PartialEq)]
+pub(crate) enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// What a config crate error tells about the value that failed
+#[derive(Default)]
+struct Description {
+    origin: Option<String>,
+    key: Option<String>,
+    expected: Option<String>,
+    found: Option<String>,
+    missing: bool,
+}
+
+/// Extracts the origin, key, expected type and found value from a config crate error
+fn describe(err: &config::ConfigError) -> Option<Description> {
+    match err {
+        config::ConfigError::Type {
+            origin,
+            unexpected,
+            expected,
+            key,
+        } => Some(Description {
+            origin: origin.clone(),
+            key: key.clone(),
+            expected: Some(expected.to_string()),
+            found: Some(unexpected.to_string()),
+            missing: false,
+        }),
+        config::ConfigError::NotFound(key) => Some(Description {
+            key: Some(key.clone()),
+            missing: true,
+            ..Default::default()
+        }),
+        config::ConfigError::At { error, origin, key } => {
+            let inner = describe(error)?;
+            let key = match (key, inner.key) {
+                (Some(parent), Some(field)) if inner.missing => {
+                    Some(format!("{}.{}", parent, field))
+                }
+                (key, inner_key) => key.clone().or(inner_key),
+            };
+            Some(Description {
+                origin: origin.clone().or(inner.origin),
+                key,
+                ..inner
+            })
+        }
+        config::ConfigError::Message(message) => {
+            // serde reports missing fields as "missing field `name`"
+            let field = message
+                .strip_prefix("missing field `")
+                .and_then(|rest| rest.split('`').next());
+            Some(Description {
+                key: field.map(str::to_string),
+                missing: field.is_some(),
+                ..Default::default()
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Gets the text of a 1-based line, including the empty line after a final newline
+fn source_line(content: &str, line: usize) -> Option<&str> {
+    let text = content.split('\n').nth(line.checked_sub(1)?)?;
+    Some(text.strip_suffix('\r').unwrap_or(text))
+}
+
+/// Finds the line and column numbers mentioned in a parser message
+///
+/// Parsers report positions as e.g. `at line 3 column 9` or `at line 3, column 9`.
+fn locate(message: &str) -> (Option<usize>, Option<usize>) {
+    let words: Vec<&str> = message
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let number_after = |label: &str| {
+        words
+            .windows(2)
+            .find(|pair| pair[0].eq_ignore_ascii_case(label))
+            .and_then(|pair| pair[1].parse::<usize>().ok())
+    };
+    let line = number_after("line").filter(|line| *line > 0);
+    let column = line.and(number_after("column"));
+    (line, column)
+}
+
+/// Writes `list[0]port`, as reported by the config crate, as `list[0].port`
+fn normalize_key_path(key: &str) -> String {
+    let mut normalized = String::with_capacity(key.len());
+    let mut chars = key.chars().peekable();
+    while let Some(c) = chars.next() {
+        normalized.push(c);
+        if c == ']'
+            && chars
+                .peek()
+                .is_some_and(|next| *next != '[' && *next != '.')
+        {
+            normalized.push('.');
+        }
+    }
+    normalized
+}
+
+/// Splits a key path such as `servers[0].port` into its steps
+pub(crate) fn key_steps(key_path: &str) -> Vec<Step> {
+    let mut steps = Vec::new();
+    for segment in key_path.split('.') {
+        let mut parts = segment.split('[');
+        if let Some(name) = parts.next().filter(|name| !name.is_empty()) {
+            steps.push(Step::Key(name.to_string()));
+        }
+        for index in parts {
+            match index.trim_end_matches(']').parse() {
+                Ok(index) => steps.push(Step::Index(index)),
+                Err(_) => steps.push(Step::Key(index.to_string())),
+            }
+        }
+    }
+    steps
+}
+
+/// Writes the steps back as a flat key, e.g. `servers[0].port`
+pub(crate) fn flat_key(steps: &[Step]) -> String {
+    let mut key = String::new();
+    for step in steps {
+        match step {
+            Step::Key(name) if key.is_empty() => key.push_str(name),
+            Step::Key(name) => key.push_str(&format!(".{}", name)),
+            Step::Index(index) => key.push_str(&format!("[{}]", index)),
+        }
+    }
+    key
+}
+
+/// Gets the 1-based line and column of a byte offset in the content
+#[cfg(feature = "toml")]
+pub(crate) fn line_column(content: &str, offset: usize) -> Option<(usize, usize)> {
+    let before = content.get(..offset)?;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let line = before.matches('\n').count() + 1;
+    Some((line, before[line_start..].chars().count() + 1))
+}
+
+/// Finds the line and the column of the value of a key by searching the text
+///
+/// Used for formats whose parser does not report positions. Each key is searched among
+/// the lines of its parent's block, i.e. the lines after the parent indented deeper than
+/// it, at the shallowest indentation of the block; comments are skipped. Nothing is found
+/// when a key occurs more than once in its block or the path goes through an array item.
+pub(crate) fn find_key(content: &str, steps: &[Step]) -> Option<(usize, usize)> {
+    let lines: Vec<(usize, &str)> = content
+        .lines()
+        .map(strip_comment)
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+
+    let mut block = &lines[..];
+    let mut found = None;
+    for step in steps {
+        let Step::Key(key) = step else {
+            return None;
+        };
+        let depth = block.iter().map(|(_, line)| indentation(line)).min()?;
+        let mut matches = block
+            .iter()
+            .enumerate()
+            .filter_map(|(position, (index, line))| {
+                let column = value_column(line, key).filter(|_| indentation(line) == depth)?;
+                Some((position, *index, column))
+            });
+        let (position, index, column) = matches.next()?;
+        if matches.next().is_some() {
+            return None;
+        }
+        found = Some((index + 1, column));
+
+        let rest = &block[position + 1..];
+        let end = rest
+            .iter()
+            .position(|(_, line)| indentation(line) <= depth)
+            .unwrap_or(rest.len());
+        block = &rest[..end];
+    }
+    found
+}
+
+/// Gets the number of leading spaces and tabs of a line
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches([' ', '\t']).len()
+}
+
+/// Cuts a line at a `#` or `//` comment outside of quotes
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..index],
+            (None, '/') if line[index..].starts_with("//") => return &line[..index],
+            (None, _) => {}
+        }
+    }
+    line
+}
+
+/// Gets the 1-based column of the value when the line declares the given key
+fn value_column(line: &str, key: &str) -> Option<usize> {
+    let chars: Vec<char> = line.chars().collect();
+    let key_chars: Vec<char> = key.chars().collect();
+    if key_chars.is_empty() {
+        return None;
+    }
+
+    for start in 0..chars.len() {
+        if !chars[start..].starts_with(&key_chars) {
+            continue;
+        }
+        let before_ok = start == 0
+            || matches!(
+                chars[start - 1],
+                ' ' | '\t' | '"' | '\'' | '[' | '{' | ',' | '-'
+            );
+        if !before_ok {
+            continue;
+        }
+
+        let mut position = start + key_chars.len();
+        if matches!(chars.get(position), Some('"') | Some('\'')) {
+            position += 1;
+        }
+        while matches!(chars.get(position), Some(' ') | Some('\t')) {
+            position += 1;
+        }
+        match chars.get(position) {
+            Some(':') | Some('=') => {
+                position += 1;
+                while matches!(chars.get(position), Some(' ') | Some('\t')) {
+                    position += 1;
+                }
+                return Some(position + 1);
+            }
+            Some(']') | Some('{') => return Some(start + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(feature = "miette")]
+impl miette::Diagnostic for crate::models::ConfigError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.hint()))
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        let source_text = self.diagnostic()?.source_text.as_ref()?;
+        Some(source_text as &dyn miette::SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let diagnostic = self.diagnostic()?;
+        let label = match (&diagnostic.expected, &diagnostic.key_path) {
+            (Some(expected), _) => Some(format!("expected {}", expected)),
+            (None, Some(key)) => Some(key.clone()),
+            (None, None) => None,
+        };
+        let span = miette::LabeledSpan::new(label, diagnostic.offset()?, 0);
+        Some(Box::new(std::iter::once(span)))
+    }
+}
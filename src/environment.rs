@@ -0,0 +1,236 @@
+use crate::fields::KnownFields;
+use crate::i18n::{Locale, Warning};
+use crate::models::{ConfigError, EnvConfig};
+use crate::report::LoadReport;
+use crate::values::{indices_to_arrays, insert_path, json_to_value};
+use config::{Map, Value, ValueKind};
+use std::env;
+
+/// Collects the environment variables that belong to the given prefix into nested values
+///
+/// `MY_APP__DATABASE__HOST=localhost` becomes `{ database: { host: "localhost" } }`.
+/// Scalar values are kept as raw strings and only converted when deserialized, according
+/// to the type of the target field: `MY_APP__ZIP=01234` stays `"01234"` for a `String`
+/// field, while `MY_APP__PORT=8080` becomes a number for a `u16` field.
+/// Depending on the `EnvConfig`, values may also be split into lists, parsed as inline
+/// JSON, and tables keyed by indices (`MY_APP__SERVERS__0__HOST`) turned into arrays.
+/// Name segments are mapped to keys with the configured `KeyMapping`, using the fields
+/// known from the target type, and aliased variables are read into their key paths.
+pub(crate) fn collect_env_values(
+    env_config: &EnvConfig,
+    known_fields: &KnownFields,
+    report: &mut LoadReport,
+) -> Result<Map<String, Value>, ConfigError> {
+    collect_values(env_config, known_fields, env::vars(), report)
+}
+
+fn collect_values<I>(
+    env_config: &EnvConfig,
+    known_fields: &KnownFields,
+    vars: I,
+    report: &mut LoadReport,
+) -> Result<Map<String, Value>, ConfigError>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let vars: Vec<(String, String)> = vars.into_iter().collect();
+    let mut root = Map::new();
+
+    // Aliased variables come first so that prefixed variables take precedence
+    for (name, raw) in &vars {
+        if let Some(key_path) = env_config.aliases.get(name) {
+            let segments: Vec<String> = key_path.split('.').map(str::to_string).collect();
+            let value = parse_value(env_config, name, key_path, raw.clone())?;
+            insert_path(&mut root, &segments, value);
+        }
+    }
+
+    let separator = env_config.get_separator();
+    for (name, raw) in vars {
+        let Some(key) = strip_env_prefix(&name, &env_config.name, separator) else {
+            continue;
+        };
+
+        let segments: Vec<&str> = key.split(separator).collect();
+        if segments.iter().any(|segment| segment.is_empty()) {
+            report.warn(Warning::EmptyKeySegment { name: &name });
+            continue;
+        }
+
+        let segments = map_segments(env_config, known_fields, &segments);
+        let value = parse_value(env_config, &name, &segments.join("."), raw)?;
+        insert_path(&mut root, &segments, value);
+    }
+
+    if env_config.indexed_arrays {
+        root = root
+            .into_iter()
+            .map(|(key, value)| (key, indices_to_arrays(value)))
+            .collect();
+    }
+
+    Ok(root)
+}
+
+/// Strips `{prefix}{separator}` from the variable name, matching the prefix case-insensitively
+pub(crate) fn strip_env_prefix<'a>(
+    name: &'a str,
+    prefix: &str,
+    separator: &str,
+) -> Option<&'a str> {
+    let head = name.get(..prefix.len())?;
+    if !head.eq_ignore_ascii_case(prefix) {
+        return None;
+    }
+    name[prefix.len()..].strip_prefix(separator)
+}
+
+/// Gets the name a variable under a deprecated prefix should be renamed to
+///
+/// e.g. `LEGACY_APP__DB__HOST` becomes `MY_APP__DB__HOST`.
+pub(crate) fn replacement_name(
+    name: &str,
+    deprecated: &EnvConfig,
+    replacement: &EnvConfig,
+) -> Option<String> {
+    if let Some(key_path) = deprecated.aliases.get(name) {
+        let segments: Vec<String> = key_path.split('.').map(str::to_uppercase).collect();
+        return Some(join_env_name(replacement, &segments));
+    }
+
+    let key = strip_env_prefix(name, &deprecated.name, deprecated.get_separator())?;
+    let segments: Vec<String> = key
+        .split(deprecated.get_separator())
+        .map(str::to_string)
+        .collect();
+    Some(join_env_name(replacement, &segments))
+}
+
+/// Gets up to five variable names whose prefix is closest to the given one
+///
+/// Helps spotting typos such as `MYAPP__PORT` or `my_app__port` for the prefix `MY_APP`.
+/// The head of each name, up to the separator or as long as the prefix, is compared
+/// to the prefix regardless of case; only the names are returned, never the values.
+pub(crate) fn similar_names<I>(prefix: &str, separator: &str, names: I) -> Vec<String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let prefix = prefix.to_uppercase();
+    let max_distance = (prefix.chars().count() / 3).max(1);
+    let mut similar: Vec<(usize, String)> = names
+        .into_iter()
+        .filter_map(|name| {
+            let upper = name.to_uppercase();
+            let head = upper.split(separator).next().unwrap_or_default();
+            let start: String = upper.chars().take(prefix.chars().count()).collect();
+            let distance = edit_distance(&prefix, head).min(edit_distance(&prefix, &start));
+            (distance <= max_distance).then_some((distance, name))
+        })
+        .collect();
+    similar.sort();
+    similar.into_iter().take(5).map(|(_, name)| name).collect()
+}
+
+/// Gets the Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn join_env_name(env_config: &EnvConfig, segments: &[String]) -> String {
+    let separator = env_config.get_separator();
+    format!(
+        "{}{}{}",
+        env_config.name,
+        separator,
+        segments.join(separator)
+    )
+}
+
+/// Maps the name segments to configuration keys, walking the known fields alongside
+fn map_segments(
+    env_config: &EnvConfig,
+    known_fields: &KnownFields,
+    segments: &[&str],
+) -> Vec<String> {
+    let mut path = String::new();
+    let mut keys = Vec::with_capacity(segments.len());
+    for segment in segments {
+        // Array indices keep their value and do not descend into the known fields
+        if segment.parse::<usize>().is_ok() {
+            keys.push(segment.to_string());
+            continue;
+        }
+
+        let key = env_config
+            .key_mapping
+            .map_segment(segment, known_fields.fields_at(&path));
+        path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+/// Converts a raw environment value according to the `EnvConfig` options
+fn parse_value(
+    env_config: &EnvConfig,
+    name: &str,
+    key: &str,
+    raw: String,
+) -> Result<Value, ConfigError> {
+    let origin = name.to_string();
+    let trimmed = raw.trim();
+
+    if env_config.json_values && (trimmed.starts_with('{') || trimmed.starts_with('[')) {
+        return match serde_json::from_str::<serde_json::Value>(trimmed) {
+            Ok(json) => Ok(json_to_value(json, &origin)),
+            Err(err) => Err(ConfigError::InvalidEnvValue {
+                name: origin,
+                reason: err.to_string(),
+                locale: Locale::from_env(),
+            }),
+        };
+    }
+
+    if let Some(list_separator) = env_config.list_separator_for(key) {
+        let items: Vec<Value> = if trimmed.is_empty() {
+            Vec::new()
+        } else {
+            raw.split(list_separator)
+                .map(|item| Value::new(Some(&origin), item.trim()))
+                .collect()
+        };
+        return Ok(Value::new(Some(&origin), ValueKind::Array(items)));
+    }
+
+    Ok(Value::new(Some(&origin), raw))
+}
+
+// Expose value collection over an explicit variable list for testing purposes
+#[cfg(test)]
+pub(crate) fn test_collect_values(
+    env_config: &EnvConfig,
+    known_fields: &KnownFields,
+    vars: Vec<(&str, &str)>,
+) -> Result<Map<String, Value>, ConfigError> {
+    collect_values(
+        env_config,
+        known_fields,
+        vars.into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string())),
+        &mut LoadReport::new(crate::i18n::Locale::En),
+    )
+}
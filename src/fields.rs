@@ -0,0 +1,273 @@
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Field names declared by a configuration type, keyed by the dotted path of their parent
+///
+/// The root struct's fields are stored under the empty path. Names are the serialized
+/// names, so `#[serde(rename = "maxConns")]` is reported as `maxConns`. Elements of
+/// sequences share the path of the sequence itself.
+///
+/// Recursive types are probed down to their first repetition: inside a struct that is
+/// already being probed, optional values are absent and sequences are empty.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct KnownFields {
+    fields: BTreeMap<String, Vec<&'static str>>,
+}
+
+impl KnownFields {
+    /// Probes the given type for the fields its `Deserialize` implementation declares
+    ///
+    /// Probing stops silently at types that cannot be probed (e.g. untagged enums or
+    /// custom implementations rejecting placeholder values); fields found so far are kept.
+    pub fn of<T>() -> Self
+    where
+        T: for<'de> de::Deserialize<'de>,
+    {
+        let known = RefCell::new(KnownFields::default());
+        let _ = T::deserialize(Probe {
+            path: String::new(),
+            known: &known,
+            ancestors: Vec::new(),
+            recursive: false,
+        });
+        known.into_inner()
+    }
+
+    /// Gets the field names declared at the given dotted path
+    pub fn fields_at(&self, path: &str) -> &[&'static str] {
+        self.fields.get(path).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Gets every known dotted key path
+    pub fn paths(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .flat_map(|(parent, names)| names.iter().map(move |name| join(parent, name)))
+            .collect()
+    }
+
+    fn record(&mut self, path: &str, names: &'static [&'static str]) {
+        let entry = self.fields.entry(path.to_string()).or_default();
+        for name in names {
+            if !entry.contains(name) {
+                entry.push(name);
+            }
+        }
+    }
+}
+
+/// Depth at which probing stops, for recursive types that cannot end at an empty value
+const MAX_DEPTH: usize = 32;
+
+/// A struct being probed, identified by its name and fields
+type StructId = (&'static str, &'static [&'static str]);
+
+fn join(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", parent, name)
+    }
+}
+
+#[derive(Debug)]
+struct ProbeError;
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "type cannot be probed")
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+impl de::Error for ProbeError {
+    fn custom<M: fmt::Display>(_msg: M) -> Self {
+        ProbeError
+    }
+}
+
+/// Deserializer feeding placeholder values while recording the struct fields it is asked for
+struct Probe<'a> {
+    path: String,
+    known: &'a RefCell<KnownFields>,
+    ancestors: Vec<StructId>, // Structs enclosing the probed value, the innermost last
+    recursive: bool,          // Whether the value is inside a repeated struct
+}
+
+impl<'de> de::Deserializer<'de> for Probe<'_> {
+    type Error = ProbeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_i64(0)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_u64(0)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_char(' ')
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        if self.recursive {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        let probe = if self.recursive { None } else { Some(self) };
+        visitor.visit_seq(ProbeSeq { probe })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_map(ProbeMap {
+            probe: self,
+            fields: &[],
+            index: 0,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        mut self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ProbeError> {
+        if self.ancestors.len() >= MAX_DEPTH {
+            return Err(ProbeError);
+        }
+        self.known.borrow_mut().record(&self.path, fields);
+        let id = (name, fields);
+        self.recursive |= self.ancestors.contains(&id);
+        self.ancestors.push(id);
+        visitor.visit_map(ProbeMap {
+            probe: self,
+            fields,
+            index: 0,
+        })
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ProbeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        self.deserialize_str(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 bytes byte_buf unit unit_struct tuple tuple_struct enum ignored_any
+    }
+}
+
+/// Yields a single probed element so that element types get probed as well
+struct ProbeSeq<'a> {
+    probe: Option<Probe<'a>>,
+}
+
+impl<'de> de::SeqAccess<'de> for ProbeSeq<'_> {
+    type Error = ProbeError;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, ProbeError> {
+        match self.probe.take() {
+            Some(probe) => seed.deserialize(probe).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct ProbeMap<'a> {
+    probe: Probe<'a>,
+    fields: &'static [&'static str],
+    index: usize,
+}
+
+impl<'de> de::MapAccess<'de> for ProbeMap<'_> {
+    type Error = ProbeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ProbeError> {
+        match self.fields.get(self.index) {
+            Some(field) => seed.deserialize((*field).into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, ProbeError> {
+        let field = self.fields[self.index];
+        self.index += 1;
+        seed.deserialize(Probe {
+            path: join(&self.probe.path, field),
+            known: self.probe.known,
+            ancestors: self.probe.ancestors.clone(),
+            recursive: self.probe.recursive,
+        })
+    }
+}
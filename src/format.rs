@@ -0,0 +1,352 @@
+use crate::diagnostic::{self, Diagnostic, Step};
+#[cfg(feature = "hocon")]
+use crate::hocon;
+use crate::i18n::Locale;
+use crate::models::ConfigError;
+#[cfg(feature = "properties")]
+use crate::properties;
+use crate::values::expand_keys;
+#[cfg(feature = "yaml")]
+use crate::yaml;
+use config::{Map, Value};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Documents of a parsed file, in order; every format but YAML has exactly one
+pub(crate) type Documents = Vec<Map<String, Value>>;
+
+/// Extensions that do not tell the format apart; the content is sniffed instead
+pub(crate) const AMBIGUOUS_EXTENSIONS: &[&str] = &["conf", "cfg", "config", "txt"];
+
+/// Extensions identifying a format
+const EXTENSIONS: &[(&str, Format)] = &[
+    ("yaml", Format::Yaml),
+    ("yml", Format::Yaml),
+    ("json", Format::Json),
+    ("toml", Format::Toml),
+    ("ini", Format::Ini),
+    ("properties", Format::Properties),
+    ("json5", Format::Json5),
+    ("ron", Format::Ron),
+    ("hocon", Format::Hocon),
+];
+
+/// Configuration file format
+///
+/// Each format is parsed only when the cargo feature of the same name is enabled.
+/// `yaml`, `json`, `toml`, `ini` and `properties` are enabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Yaml,
+    Json,
+    Toml,
+    Ini,
+    Properties,
+    Json5,
+    Ron,
+    Hocon,
+}
+
+impl Format {
+    /// Formats in the order they are tried when sniffing content
+    ///
+    /// YAML accepts almost any text as a scalar, so it is only chosen when the content
+    /// parses as a table; `.properties` is only chosen when every entry has an explicit
+    /// separator and there are no sections; INI is the most lenient and is tried last.
+    const SNIFF_ORDER: &'static [Format] = &[
+        Format::Json,
+        Format::Json5,
+        Format::Toml,
+        Format::Ron,
+        Format::Yaml,
+        Format::Hocon,
+        Format::Properties,
+        Format::Ini,
+    ];
+
+    /// Gets the format for a file extension, if the extension identifies one
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.to_lowercase();
+        EXTENSIONS
+            .iter()
+            .find(|(ext, _)| *ext == extension)
+            .map(|(_, format)| *format)
+    }
+
+    /// Checks whether the cargo feature needed to parse this format is enabled
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Format::Yaml => cfg!(feature = "yaml"),
+            Format::Json => cfg!(feature = "json"),
+            Format::Toml => cfg!(feature = "toml"),
+            Format::Ini => cfg!(feature = "ini"),
+            Format::Properties => cfg!(feature = "properties"),
+            Format::Json5 => cfg!(feature = "json5"),
+            Format::Ron => cfg!(feature = "ron"),
+            Format::Hocon => cfg!(feature = "hocon"),
+        }
+    }
+
+    /// Gets the name of the cargo feature needed to parse this format
+    pub fn feature(&self) -> &'static str {
+        match self {
+            Format::Yaml => "yaml",
+            Format::Json => "json",
+            Format::Toml => "toml",
+            Format::Ini => "ini",
+            Format::Properties => "properties",
+            Format::Json5 => "json5",
+            Format::Ron => "ron",
+            Format::Hocon => "hocon",
+        }
+    }
+
+    /// Parses the content into documents of nested values, using `origin` to describe
+    /// where they come from
+    ///
+    /// For multi-document YAML, `profile` selects the documents tagged with it.
+    pub(crate) fn parse(
+        &self,
+        content: &str,
+        origin: &String,
+        profile: Option<&str>,
+    ) -> Result<Documents, ConfigError> {
+        let documents = self
+            .parse_raw(content, origin, profile)
+            .ok_or_else(|| self.not_enabled(Path::new(origin)))?
+            .map_err(|cause| parse_error(origin, Some(content), cause))?;
+
+        // HOCON resolves dotted keys itself; quoted keys may contain dots
+        if *self == Format::Hocon {
+            return Ok(documents);
+        }
+        Ok(documents
+            .into_iter()
+            .map(expand_keys)
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Parses the content with the parser of this format, or returns `None` if it is disabled
+    fn parse_raw(
+        &self,
+        content: &str,
+        origin: &String,
+        profile: Option<&str>,
+    ) -> Option<Result<Documents, Box<dyn Error + Send + Sync>>> {
+        let single = |values: Map<String, Value>| vec![values];
+        match self {
+            #[cfg(feature = "yaml")]
+            Format::Yaml => Some(yaml::parse(content, origin, profile)),
+            #[cfg(feature = "json")]
+            Format::Json => Some(parse_with(config::FileFormat::Json, content, origin).map(single)),
+            #[cfg(feature = "toml")]
+            Format::Toml => Some(parse_with(config::FileFormat::Toml, content, origin).map(single)),
+            #[cfg(feature = "ini")]
+            Format::Ini => Some(parse_with(config::FileFormat::Ini, content, origin).map(single)),
+            #[cfg(feature = "properties")]
+            Format::Properties => Some(
+                properties::parse(content, origin)
+                    .map_err(Into::into)
+                    .map(single),
+            ),
+            #[cfg(feature = "json5")]
+            Format::Json5 => {
+                Some(parse_with(config::FileFormat::Json5, content, origin).map(single))
+            }
+            #[cfg(feature = "ron")]
+            Format::Ron => Some(parse_with(config::FileFormat::Ron, content, origin).map(single)),
+            #[cfg(feature = "hocon")]
+            Format::Hocon => Some(
+                hocon::parse(content, origin)
+                    .map_err(Into::into)
+                    .map(single),
+            ),
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = (content, origin, profile, single);
+                None
+            }
+        }
+    }
+
+    /// Reads and parses a file into documents, using `origin` to describe where its values come from
+    ///
+    /// HOCON files are loaded through their path so that `include` statements resolve
+    /// relative to the file.
+    pub(crate) fn parse_file(
+        &self,
+        path: &Path,
+        origin: &String,
+        profile: Option<&str>,
+    ) -> Result<Documents, ConfigError> {
+        if !self.is_enabled() {
+            return Err(self.not_enabled(path));
+        }
+
+        #[cfg(feature = "hocon")]
+        if *self == Format::Hocon {
+            return hocon::parse_file(path, origin)
+                .map(|values| vec![values])
+                .map_err(|err| parse_error(origin, None, err));
+        }
+
+        let content = fs::read_to_string(path).map_err(|err| config::ConfigError::FileParse {
+            uri: Some(origin.clone()),
+            cause: Box::new(err),
+        })?;
+        self.parse(&content, origin, profile)
+    }
+
+    /// Builds the error reported when the feature of this format is disabled
+    pub(crate) fn not_enabled(&self, path: &Path) -> ConfigError {
+        ConfigError::FormatNotEnabled {
+            path: path.to_path_buf(),
+            format: *self,
+            locale: Locale::from_env(),
+        }
+    }
+
+    /// Finds the 1-based line and column of the value at the key path in the content
+    ///
+    /// YAML and TOML positions come from their parser; the other formats are searched by
+    /// their text. For multi-document YAML, `profile` selects the documents searched.
+    pub(crate) fn locate(
+        &self,
+        content: &str,
+        steps: &[Step],
+        profile: Option<&str>,
+    ) -> Option<(usize, usize)> {
+        match self {
+            #[cfg(feature = "yaml")]
+            Format::Yaml => yaml::locate(content, steps, profile),
+            #[cfg(feature = "toml")]
+            Format::Toml => locate_toml(content, steps),
+            Format::Ini => locate_ini(content, steps),
+            #[cfg(feature = "properties")]
+            Format::Properties => properties::locate(content, &diagnostic::flat_key(steps)),
+            _ => {
+                let _ = profile;
+                diagnostic::find_key(content, steps)
+            }
+        }
+    }
+
+    /// Checks whether the content parses as a non-empty table in this format
+    fn matches(&self, content: &str) -> bool {
+        #[cfg(feature = "properties")]
+        if *self == Format::Properties && !properties::is_likely(content) {
+            return false;
+        }
+        self.is_enabled()
+            && self
+                .parse(content, &String::new(), None)
+                .map(|documents| documents.iter().any(|table| !table.is_empty()))
+                .unwrap_or(false)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.feature())
+    }
+}
+
+/// Lists the extensions of the enabled formats, with the leading dot
+pub(crate) fn supported_extensions() -> Vec<String> {
+    EXTENSIONS
+        .iter()
+        .filter(|(_, format)| format.is_enabled())
+        .map(|(ext, _)| format!(".{}", ext))
+        .collect()
+}
+
+/// Guesses the format of the content by trying each enabled format in turn
+pub(crate) fn sniff_format(content: &str) -> Option<Format> {
+    Format::SNIFF_ORDER
+        .iter()
+        .copied()
+        .find(|format| format.matches(content))
+}
+
+/// Parses the content with one of the formats of the config crate
+#[allow(dead_code)]
+fn parse_with(
+    file_format: config::FileFormat,
+    content: &str,
+    origin: &String,
+) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+    config::Format::parse(&file_format, Some(origin), content)
+}
+
+/// Finds the value at the key path through the spans of the TOML parser
+#[cfg(feature = "toml")]
+fn locate_toml(content: &str, steps: &[Step]) -> Option<(usize, usize)> {
+    use toml::de::{DeTable, DeValue};
+
+    let root = DeTable::parse(content).ok()?;
+    let mut table = Some(root.get_ref());
+    let mut array: Option<&[toml::Spanned<DeValue>]> = None;
+    let mut span = None;
+    for step in steps {
+        let value = match step {
+            Step::Key(name) => table?.iter().find(|(key, _)| key.get_ref() == name)?.1,
+            Step::Index(index) => array?.get(*index)?,
+        };
+        span = Some(value.span());
+        (table, array) = match value.get_ref() {
+            DeValue::Table(inner) => (Some(inner), None),
+            DeValue::Array(items) => (None, Some(&items[..])),
+            _ => (None, None),
+        };
+    }
+    diagnostic::line_column(content, span?.start)
+}
+
+/// Finds an entry of an INI file, i.e. a key under its `[section]` or before any section
+fn locate_ini(content: &str, steps: &[Step]) -> Option<(usize, usize)> {
+    let key = diagnostic::flat_key(steps);
+    let mut section: Option<&str> = None;
+    let mut found = None;
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with(';') || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            section = Some(name.trim());
+            continue;
+        }
+        let Some(separator) = line.find(['=', ':']) else {
+            continue;
+        };
+        let entry_key = match section {
+            Some(section) => format!("{}.{}", section, line[..separator].trim()),
+            None => line[..separator].trim().to_string(),
+        };
+        if entry_key != key {
+            continue;
+        }
+        if found.is_some() {
+            return None;
+        }
+        let value = &line[separator + 1..];
+        let offset = separator + 1 + value.len() - value.trim_start().len();
+        found = Some((index + 1, line[..offset].chars().count() + 1));
+    }
+    found
+}
+
+/// Describes an error raised while parsing a file, with the content when available
+fn parse_error<E>(origin: &str, content: Option<&str>, cause: E) -> ConfigError
+where
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    ConfigError::Parse(Box::new(Diagnostic::parse(origin, content, cause.into())))
+}
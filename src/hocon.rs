@@ -0,0 +1,53 @@
+use ::hocon::{Error, Hocon, HoconLoader};
+use config::{Map, Value, ValueKind};
+use std::path::Path;
+
+/// Parses HOCON content into nested values
+///
+/// Substitutions are resolved, but `include` is only supported when loading from a file.
+pub(crate) fn parse(content: &str, origin: &String) -> Result<Map<String, Value>, Error> {
+    let hocon = HoconLoader::new().strict().load_str(content)?.hocon()?;
+    to_table(hocon, origin)
+}
+
+/// Parses a HOCON file into nested values
+///
+/// Substitutions are resolved and `include` statements are read relative to the file.
+pub(crate) fn parse_file(path: &Path, origin: &String) -> Result<Map<String, Value>, Error> {
+    let hocon = HoconLoader::new().strict().load_file(path)?.hocon()?;
+    to_table(hocon, origin)
+}
+
+/// Converts the root of a HOCON document, which must be an object, into a table
+fn to_table(hocon: Hocon, origin: &String) -> Result<Map<String, Value>, Error> {
+    match to_value(hocon, origin)?.kind {
+        ValueKind::Table(table) => Ok(table),
+        _ => Err(Error::Deserialization {
+            message: "the root of a HOCON document must be an object".to_string(),
+        }),
+    }
+}
+
+/// Converts a HOCON value into a configuration value
+fn to_value(hocon: Hocon, origin: &String) -> Result<Value, Error> {
+    let kind = match hocon {
+        Hocon::Real(f) => ValueKind::Float(f),
+        Hocon::Integer(i) => ValueKind::I64(i),
+        Hocon::String(s) => ValueKind::String(s),
+        Hocon::Boolean(b) => ValueKind::Boolean(b),
+        Hocon::Null => ValueKind::Nil,
+        Hocon::Array(items) => ValueKind::Array(
+            items
+                .into_iter()
+                .map(|item| to_value(item, origin))
+                .collect::<Result<_, _>>()?,
+        ),
+        Hocon::Hash(hash) => ValueKind::Table(
+            hash.into_iter()
+                .map(|(key, value)| Ok((key, to_value(value, origin)?)))
+                .collect::<Result<_, Error>>()?,
+        ),
+        Hocon::BadValue(err) => return Err(err),
+    };
+    Ok(Value::new(Some(origin), kind))
+}
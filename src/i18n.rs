@@ -0,0 +1,621 @@
+use crate::diagnostic::Diagnostic;
+use crate::models::ConfigError;
+use crate::telemetry;
+use std::env;
+use std::fmt;
+use std::path::Path;
+
+/// Environment variable opting in to localized messages
+///
+/// Messages are in English unless it is set; `system` follows the POSIX locale variables.
+const LANG_VAR: &str = "DUMBO_CONFIG_LANG";
+
+/// Environment variables of the system locale, by priority
+const SYSTEM_LOCALE_VARS: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// Language of error messages, hints and warnings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    /// English (default)
+    #[default]
+    En,
+    /// Simplified Chinese
+    ZhCn,
+}
+
+impl Locale {
+    /// Parses a language tag or a `LANG`-style value, e.g. `zh-CN`, `zh_CN.UTF-8` or `en_US`
+    ///
+    /// `C` and `POSIX` are English; other languages are not supported and give `None`.
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default();
+        let tag = tag.replace('_', "-").to_lowercase();
+        let language = tag.split('-').next().unwrap_or_default();
+        match language {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "zh" if matches!(tag.as_str(), "zh" | "zh-cn" | "zh-sg" | "zh-hans")
+                || tag.starts_with("zh-hans-") =>
+            {
+                Some(Locale::ZhCn)
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the locale from `DUMBO_CONFIG_LANG`, e.g. `zh-CN`
+    ///
+    /// English unless the variable is set. With `DUMBO_CONFIG_LANG=system`, the first of
+    /// `LC_ALL`, `LC_MESSAGES` and `LANG` that is set and not empty decides. Unsupported
+    /// languages fall back to English.
+    pub fn from_env() -> Locale {
+        Locale::from_vars(|name| env::var(name).ok())
+    }
+
+    /// Gets the locale from the variables returned by `lookup`
+    pub(crate) fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> Locale {
+        let value = match lookup(LANG_VAR) {
+            Some(value) if value.eq_ignore_ascii_case("system") => SYSTEM_LOCALE_VARS
+                .iter()
+                .filter_map(|name| lookup(name))
+                .find(|value| !value.is_empty()),
+            value => value,
+        };
+        value
+            .and_then(|value| Locale::parse(&value))
+            .unwrap_or_default()
+    }
+
+    /// Gets the language tag, e.g. `zh-CN`
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tag())
+    }
+}
+
+/// Gets the given locale, or the one of the environment when not set
+pub(crate) fn resolve(locale: Option<Locale>) -> Locale {
+    locale.unwrap_or_else(Locale::from_env)
+}
+
+/// Warnings logged while loading configuration
+pub(crate) enum Warning<'a> {
+    /// No variable uses the prefix, so its layer is skipped
+    NoEnvVars { prefix: &'a str },
+    /// A variable uses a deprecated prefix
+    DeprecatedPrefix {
+        name: &'a str,
+        prefix: &'a str,
+        replacement: Option<&'a str>,
+    },
+    /// A variable name has an empty key segment, e.g. `MY_APP____PORT`
+    EmptyKeySegment { name: &'a str },
+    /// The loaded configuration cannot be serialized for SHOW_SETTINGS
+    SerializeFailed { error: &'a dyn fmt::Display },
+    /// The loaded configuration cannot be written to the file SHOW_SETTINGS names
+    ShowSettingsWriteFailed {
+        path: &'a Path,
+        error: &'a dyn fmt::Display,
+    },
+    /// A reload of a `ConfigHandle` failed, so the given version stays published
+    ReloadFailed {
+        version: u64,
+        error: &'a ConfigError,
+    },
+    /// The configurations cannot be compared to notify the subscribers of a `ConfigHandle`
+    NotifyFailed { error: &'a dyn fmt::Display },
+    /// A file loaded by the legacy loaders cannot be parsed
+    #[cfg(feature = "yaml")]
+    FileParseFailed {
+        path: &'a Path,
+        error: &'a dyn fmt::Display,
+    },
+    /// A file is loaded by the legacy loaders while the `yaml` feature is disabled
+    #[cfg(not(feature = "yaml"))]
+    YamlNotEnabled { path: &'a Path },
+}
+
+impl Warning<'_> {
+    /// Gets the text of the warning in the given locale
+    pub(crate) fn text(&self, locale: Locale) -> String {
+        match locale {
+            Locale::En => self.text_en(),
+            Locale::ZhCn => self.text_zh_cn(),
+        }
+    }
+
+    /// Logs the warning in the given locale, through `tracing` too when the feature is enabled
+    ///
+    /// A warning about an error also carries the code, key path and source of the error.
+    pub(crate) fn log(&self, locale: Locale) {
+        match self {
+            Warning::ReloadFailed { error, .. } => {
+                telemetry::error_warning(self.code(), &self.text(locale), error)
+            }
+            _ => telemetry::warning(self.code(), &self.text(locale)),
+        }
+    }
+
+    /// Gets the stable code of the warning, which does not change with the locale
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            Warning::NoEnvVars { .. } => "dumbo_config::no_env_vars",
+            Warning::DeprecatedPrefix { .. } => "dumbo_config::deprecated_prefix",
+            Warning::EmptyKeySegment { .. } => "dumbo_config::empty_key_segment",
+            Warning::SerializeFailed { .. } => "dumbo_config::serialize_failed",
+            Warning::ShowSettingsWriteFailed { .. } => "dumbo_config::show_settings_write_failed",
+            Warning::ReloadFailed { .. } => "dumbo_config::reload_failed",
+            Warning::NotifyFailed { .. } => "dumbo_config::notify_failed",
+            #[cfg(feature = "yaml")]
+            Warning::FileParseFailed { .. } => "dumbo_config::file_parse_failed",
+            #[cfg(not(feature = "yaml"))]
+            Warning::YamlNotEnabled { .. } => "dumbo_config::yaml_not_enabled",
+        }
+    }
+
+    fn text_en(&self) -> String {
+        match self {
+            Warning::NoEnvVars { prefix } => format!(
+                "No environment variables found with prefix: '{}'. Skipping environment variable loading.",
+                prefix
+            ),
+            Warning::DeprecatedPrefix {
+                name,
+                prefix,
+                replacement: Some(replacement),
+            } => format!(
+                "Environment variable '{}' uses deprecated prefix '{}'. Please use '{}' instead.",
+                name, prefix, replacement
+            ),
+            Warning::DeprecatedPrefix { name, prefix, .. } => format!(
+                "Environment variable '{}' uses deprecated prefix '{}'.",
+                name, prefix
+            ),
+            Warning::EmptyKeySegment { name } => format!(
+                "Ignoring environment variable '{}': empty key segment",
+                name
+            ),
+            Warning::SerializeFailed { error } => format!(
+                "Failed to serialize configuration for logging: {}",
+                error
+            ),
+            Warning::ShowSettingsWriteFailed { path, error } => format!(
+                "Failed to write configuration to {:?} (SHOW_SETTINGS): {}",
+                path, error
+            ),
+            Warning::ReloadFailed { version, error } => format!(
+                "Configuration reload failed, keeping version {}: {}",
+                version,
+                error.message_in(Locale::En)
+            ),
+            Warning::NotifyFailed { error } => format!(
+                "Failed to compare configuration versions for subscribers: {}",
+                error
+            ),
+            #[cfg(feature = "yaml")]
+            Warning::FileParseFailed { path, error } => format!(
+                "Failed to parse configuration file {:?}: {}",
+                path, error
+            ),
+            #[cfg(not(feature = "yaml"))]
+            Warning::YamlNotEnabled { path } => format!(
+                "Cannot load configuration file {:?}: the 'yaml' feature of dumbo_config is disabled",
+                path
+            ),
+        }
+    }
+
+    fn text_zh_cn(&self) -> String {
+        match self {
+            Warning::NoEnvVars { prefix } => {
+                format!("未找到前缀为 '{}' 的环境变量，跳过环境变量加载。", prefix)
+            }
+            Warning::DeprecatedPrefix {
+                name,
+                prefix,
+                replacement: Some(replacement),
+            } => format!(
+                "环境变量 '{}' 使用了已弃用的前缀 '{}'，请改用 '{}'。",
+                name, prefix, replacement
+            ),
+            Warning::DeprecatedPrefix { name, prefix, .. } => {
+                format!("环境变量 '{}' 使用了已弃用的前缀 '{}'。", name, prefix)
+            }
+            Warning::EmptyKeySegment { name } => {
+                format!("忽略环境变量 '{}'：键名中存在空段", name)
+            }
+            Warning::SerializeFailed { error } => {
+                format!("无法序列化配置以输出日志：{}", error)
+            }
+            Warning::ShowSettingsWriteFailed { path, error } => {
+                format!("无法将配置写入 {:?}（SHOW_SETTINGS）：{}", path, error)
+            }
+            Warning::ReloadFailed { version, error } => format!(
+                "配置重载失败，继续使用版本 {}：{}",
+                version,
+                error.message_in(Locale::ZhCn)
+            ),
+            Warning::NotifyFailed { error } => {
+                format!("无法比较配置版本以通知订阅者：{}", error)
+            }
+            #[cfg(feature = "yaml")]
+            Warning::FileParseFailed { path, error } => {
+                format!("解析配置文件 {:?} 失败：{}", path, error)
+            }
+            #[cfg(not(feature = "yaml"))]
+            Warning::YamlNotEnabled { path } => format!(
+                "无法加载配置文件 {:?}：dumbo_config 的 'yaml' 特性未启用",
+                path
+            ),
+        }
+    }
+}
+
+/// Gets what went wrong, without the hint
+pub(crate) fn message(err: &ConfigError, locale: Locale) -> String {
+    match locale {
+        Locale::En => message_en(err),
+        Locale::ZhCn => message_zh_cn(err),
+    }
+}
+
+/// Gets how to fix the error
+pub(crate) fn hint(err: &ConfigError, locale: Locale) -> String {
+    match locale {
+        Locale::En => hint_en(err),
+        Locale::ZhCn => hint_zh_cn(err),
+    }
+}
+
+/// Describes where the error is, e.g. `configuration file "app.yaml" at line 3, column 9`
+pub(crate) fn location(diagnostic: &Diagnostic, locale: Locale) -> String {
+    match locale {
+        Locale::En => location_en(diagnostic),
+        Locale::ZhCn => location_zh_cn(diagnostic),
+    }
+}
+
+fn message_en(err: &ConfigError) -> String {
+    match err {
+        ConfigError::Config { error: err, .. } => format!("Config error: {}", err),
+        ConfigError::FileNotFound { path, .. } => {
+            format!("Configuration file not found: {:?}", path)
+        }
+        ConfigError::ShowSettingsParseError { value, .. } => format!(
+            "Cannot parse SHOW_SETTINGS environment variable '{}' as boolean",
+            value
+        ),
+        ConfigError::InvalidLoadingParam { .. } => "No configuration source provided.".to_string(),
+        ConfigError::InvalidEnvConfig {
+            prefix, separator, ..
+        } => format!(
+            "Invalid environment configuration: env prefix '{}' contains separator '{}'.\n\
+             This will cause configuration loading to fail.",
+            prefix, separator
+        ),
+        ConfigError::InvalidMergeStrategy { key, value, .. } => format!(
+            "Invalid merge strategy '{}' declared for key '{}'.",
+            value, key
+        ),
+        ConfigError::UnknownFileFormat {
+            path, extension, ..
+        } => match extension {
+            Some(ext) => format!(
+                "Cannot determine the format of configuration file {:?}: extension '.{}' is not recognized.",
+                path, ext
+            ),
+            None => format!(
+                "Cannot determine the format of configuration file {:?} from its content.",
+                path
+            ),
+        },
+        ConfigError::FormatNotEnabled { path, format, .. } => format!(
+            "Configuration file {:?} is in {} format, but support for {} is not compiled in.",
+            path, format, format
+        ),
+        ConfigError::Parse(diagnostic) => format!(
+            "Failed to parse {}:\n{}",
+            location_en(diagnostic),
+            diagnostic.message
+        ),
+        ConfigError::Deserialize(diagnostic) => match (
+            &diagnostic.key_path,
+            &diagnostic.expected,
+            &diagnostic.found,
+        ) {
+            (Some(key), _, _) if diagnostic.missing => {
+                format!("Missing configuration key '{}'.", key)
+            }
+            (Some(key), Some(expected), Some(found)) => format!(
+                "Invalid value for key '{}' in {}: found {}, expected {}.",
+                key,
+                location_en(diagnostic),
+                found,
+                expected
+            ),
+            _ => format!(
+                "Invalid configuration in {}: {}.",
+                location_en(diagnostic),
+                diagnostic.message
+            ),
+        },
+        ConfigError::InvalidEnvValue { name, reason, .. } => format!(
+            "Invalid value in environment variable '{}': {}.",
+            name, reason
+        ),
+        ConfigError::MissingEnvVars {
+            prefix, similar, ..
+        } if similar.is_empty() => format!(
+            "No environment variables found with required prefix '{}'.",
+            prefix
+        ),
+        ConfigError::MissingEnvVars {
+            prefix, similar, ..
+        } => format!(
+            "No environment variables found with required prefix '{}'.\n\
+             Similar variables found: {}.",
+            prefix,
+            similar.join(", ")
+        ),
+        ConfigError::ValidationFailed { reason, .. } => {
+            format!("Configuration rejected by validation: {}.", reason)
+        }
+        ConfigError::SnapshotNotFound { version, .. } => format!(
+            "No configuration snapshot with version {} in the reload history.",
+            version
+        ),
+        ConfigError::SourceFailed { name, reason, .. } => format!(
+            "Failed to load configuration source '{}': {}.",
+            name, reason
+        ),
+        ConfigError::LoadCancelled { .. } => "Configuration loading was cancelled.".to_string(),
+    }
+}
+
+fn message_zh_cn(err: &ConfigError) -> String {
+    match err {
+        ConfigError::Config { error: err, .. } => format!("配置错误：{}", err),
+        ConfigError::FileNotFound { path, .. } => format!("找不到配置文件：{:?}", path),
+        ConfigError::ShowSettingsParseError { value, .. } => {
+            format!("无法将环境变量 SHOW_SETTINGS 的值 '{}' 解析为布尔值", value)
+        }
+        ConfigError::InvalidLoadingParam { .. } => "未提供任何配置来源。".to_string(),
+        ConfigError::InvalidEnvConfig {
+            prefix, separator, ..
+        } => format!(
+            "环境变量配置无效：前缀 '{}' 包含分隔符 '{}'。\n\
+             这会导致配置加载失败。",
+            prefix, separator
+        ),
+        ConfigError::InvalidMergeStrategy { key, value, .. } => {
+            format!("键 '{}' 声明的合并策略 '{}' 无效。", key, value)
+        }
+        ConfigError::UnknownFileFormat {
+            path, extension, ..
+        } => match extension {
+            Some(ext) => format!(
+                "无法确定配置文件 {:?} 的格式：不支持扩展名 '.{}'。",
+                path, ext
+            ),
+            None => format!("无法根据内容确定配置文件 {:?} 的格式。", path),
+        },
+        ConfigError::FormatNotEnabled { path, format, .. } => format!(
+            "配置文件 {:?} 为 {} 格式，但编译时未启用 {} 支持。",
+            path, format, format
+        ),
+        ConfigError::Parse(diagnostic) => format!(
+            "解析 {} 失败：\n{}",
+            location_zh_cn(diagnostic),
+            diagnostic.message
+        ),
+        ConfigError::Deserialize(diagnostic) => match (
+            &diagnostic.key_path,
+            &diagnostic.expected,
+            &diagnostic.found,
+        ) {
+            (Some(key), _, _) if diagnostic.missing => {
+                format!("缺少配置键 '{}'。", key)
+            }
+            (Some(key), Some(expected), Some(found)) => format!(
+                "{} 中键 '{}' 的值无效：实际为 {}，应为 {}。",
+                location_zh_cn(diagnostic),
+                key,
+                found,
+                expected
+            ),
+            _ => format!(
+                "{} 中的配置无效：{}。",
+                location_zh_cn(diagnostic),
+                diagnostic.message
+            ),
+        },
+        ConfigError::InvalidEnvValue { name, reason, .. } => {
+            format!("环境变量 '{}' 的值无效：{}。", name, reason)
+        }
+        ConfigError::MissingEnvVars {
+            prefix, similar, ..
+        } if similar.is_empty() => {
+            format!("未找到前缀为 '{}' 的必需环境变量。", prefix)
+        }
+        ConfigError::MissingEnvVars {
+            prefix, similar, ..
+        } => format!(
+            "未找到前缀为 '{}' 的必需环境变量。\n\
+             找到的相近变量：{}。",
+            prefix,
+            similar.join("、")
+        ),
+        ConfigError::ValidationFailed { reason, .. } => format!("配置未通过校验：{}。", reason),
+        ConfigError::SnapshotNotFound { version, .. } => {
+            format!("重载历史中没有版本为 {} 的配置快照。", version)
+        }
+        ConfigError::SourceFailed { name, reason, .. } => {
+            format!("加载配置来源 '{}' 失败：{}。", name, reason)
+        }
+        ConfigError::LoadCancelled { .. } => "配置加载已被取消。".to_string(),
+    }
+}
+
+fn hint_en(err: &ConfigError) -> String {
+    match err {
+        ConfigError::Config { .. } => {
+            "Please check the configuration file and environment variables.".to_string()
+        }
+        ConfigError::FileNotFound { .. } => {
+            "Please check the 'file' parameter and that the file is deployed with the application."
+                .to_string()
+        }
+        ConfigError::ShowSettingsParseError { .. } => {
+            "Please set it to one of: true, false, 1, 0, yes, no, on, off,\n\
+             or to a format (json, yaml, toml, env, table, diff) optionally followed by ':' and\n\
+             a destination (log, stderr or a file path), e.g. 'yaml:stderr'."
+                .to_string()
+        }
+        ConfigError::InvalidLoadingParam { .. } => "Please configure at least one of:\n\
+             - Configuration file (set the 'file' parameter)\n\
+             - Environment variables (set the 'env_prefix' parameter with at least one valid prefix)"
+            .to_string(),
+        ConfigError::InvalidEnvConfig { .. } => {
+            "Please choose a prefix that doesn't contain the separator,\n\
+             or use a different separator character."
+                .to_string()
+        }
+        ConfigError::InvalidMergeStrategy { .. } => {
+            "Supported strategies are: replace, append, prepend, merge_by_key:<field>.".to_string()
+        }
+        ConfigError::UnknownFileFormat { .. } => format!(
+            "Please use one of the extensions {},\n\
+             or set the 'file_format' parameter explicitly.",
+            crate::format::supported_extensions().join(", ")
+        ),
+        ConfigError::FormatNotEnabled { format, .. } => format!(
+            "Please enable the '{}' feature of dumbo_config in Cargo.toml,\n\
+             or convert the file to one of the extensions {}.",
+            format.feature(),
+            crate::format::supported_extensions().join(", ")
+        ),
+        ConfigError::Parse(_) => "Please fix the syntax of the configuration file.".to_string(),
+        ConfigError::Deserialize(_) => {
+            "Please set the key in the configuration file or environment variables\n\
+             with a value matching the type expected by the application."
+                .to_string()
+        }
+        ConfigError::InvalidEnvValue { .. } => {
+            "Please fix the value or unset the variable.".to_string()
+        }
+        ConfigError::MissingEnvVars { .. } => {
+            "Please check the variable names in the deployment manifest,\n\
+             or remove `required()` from the env prefix if the variables are optional."
+                .to_string()
+        }
+        ConfigError::ValidationFailed { .. } => {
+            "Please fix the configuration so that it passes the validator;\n\
+             the previous configuration stays in use until then."
+                .to_string()
+        }
+        ConfigError::SnapshotNotFound { .. } => {
+            "Please pick a version listed by `ConfigHandle::history`,\n\
+             or keep more snapshots with `with_history_size`."
+                .to_string()
+        }
+        ConfigError::SourceFailed { .. } => {
+            "Please check that the source is reachable and returns a JSON object.".to_string()
+        }
+        ConfigError::LoadCancelled { .. } => {
+            "Please load the configuration again while the tokio runtime is running.".to_string()
+        }
+    }
+}
+
+fn hint_zh_cn(err: &ConfigError) -> String {
+    match err {
+        ConfigError::Config { .. } => "请检查配置文件和环境变量。".to_string(),
+        ConfigError::FileNotFound { .. } => {
+            "请检查 'file' 参数，并确认该文件已随应用一起部署。".to_string()
+        }
+        ConfigError::ShowSettingsParseError { .. } => {
+            "请将其设置为以下值之一：true、false、1、0、yes、no、on、off，\n\
+             或设置为一种格式（json、yaml、toml、env、table、diff），可在其后加上 ':' 和\n\
+             输出位置（log、stderr 或文件路径），例如 'yaml:stderr'。"
+                .to_string()
+        }
+        ConfigError::InvalidLoadingParam { .. } => "请至少配置以下一项：\n\
+             - 配置文件（设置 'file' 参数）\n\
+             - 环境变量（设置 'env_prefix' 参数，并至少提供一个有效前缀）"
+            .to_string(),
+        ConfigError::InvalidEnvConfig { .. } => "请选择不包含分隔符的前缀，\n\
+             或改用其他分隔符。"
+            .to_string(),
+        ConfigError::InvalidMergeStrategy { .. } => {
+            "支持的策略有：replace、append、prepend、merge_by_key:<field>。".to_string()
+        }
+        ConfigError::UnknownFileFormat { .. } => format!(
+            "请使用以下扩展名之一：{}，\n\
+             或显式设置 'file_format' 参数。",
+            crate::format::supported_extensions().join(", ")
+        ),
+        ConfigError::FormatNotEnabled { format, .. } => format!(
+            "请在 Cargo.toml 中启用 dumbo_config 的 '{}' 特性，\n\
+             或将文件转换为以下扩展名之一：{}。",
+            format.feature(),
+            crate::format::supported_extensions().join(", ")
+        ),
+        ConfigError::Parse(_) => "请修正配置文件的语法。".to_string(),
+        ConfigError::Deserialize(_) => "请在配置文件或环境变量中设置该键，\n\
+             且取值须符合应用期望的类型。"
+            .to_string(),
+        ConfigError::InvalidEnvValue { .. } => "请修正该值，或删除该环境变量。".to_string(),
+        ConfigError::MissingEnvVars { .. } => "请检查部署清单中的变量名，\n\
+             如果这些变量是可选的，请去掉该前缀的 `required()`。"
+            .to_string(),
+        ConfigError::ValidationFailed { .. } => "请修正配置使其通过校验；\n\
+             在此之前将继续使用之前的配置。"
+            .to_string(),
+        ConfigError::SnapshotNotFound { .. } => "请选择 `ConfigHandle::history` 列出的版本，\n\
+             或通过 `with_history_size` 保留更多快照。"
+            .to_string(),
+        ConfigError::SourceFailed { .. } => {
+            "请检查该来源是否可访问，并返回 JSON 对象。".to_string()
+        }
+        ConfigError::LoadCancelled { .. } => "请在 tokio 运行时运行期间重新加载配置。".to_string(),
+    }
+}
+
+fn location_en(diagnostic: &Diagnostic) -> String {
+    let mut location = match (&diagnostic.path, &diagnostic.origin) {
+        (Some(path), _) => format!("configuration file {:?}", path),
+        (None, Some(origin)) => format!("'{}'", origin),
+        (None, None) => "the merged configuration".to_string(),
+    };
+    match (diagnostic.line, diagnostic.column) {
+        (Some(line), Some(column)) => {
+            location.push_str(&format!(" at line {}, column {}", line, column))
+        }
+        (Some(line), None) => location.push_str(&format!(" at line {}", line)),
+        _ => {}
+    }
+    location
+}
+
+fn location_zh_cn(diagnostic: &Diagnostic) -> String {
+    let mut location = match (&diagnostic.path, &diagnostic.origin) {
+        (Some(path), _) => format!("配置文件 {:?}", path),
+        (None, Some(origin)) => format!("'{}'", origin),
+        (None, None) => "合并后的配置".to_string(),
+    };
+    match (diagnostic.line, diagnostic.column) {
+        (Some(line), Some(column)) => {
+            location.push_str(&format!(" 第 {} 行第 {} 列", line, column))
+        }
+        (Some(line), None) => location.push_str(&format!(" 第 {} 行", line)),
+        _ => {}
+    }
+    location
+}
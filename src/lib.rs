@@ -3,15 +3,72 @@
 //! This library provides a flexible way to load configuration from files and environment variables,
 //! with detailed logging and comprehensive error handling.
 
+#[cfg(feature = "tokio")]
+pub mod async_loading;
 pub mod config;
+pub mod diagnostic;
+mod environment;
+pub mod fields;
+pub mod format;
+#[cfg(feature = "hocon")]
+mod hocon;
+pub mod i18n;
 pub mod loading;
+pub mod merge;
 pub mod models;
+#[cfg(feature = "properties")]
+mod properties;
+pub mod reload;
+pub mod report;
+mod show_settings;
+#[cfg(all(unix, feature = "signal"))]
+pub mod signal;
+pub mod subscription;
+mod telemetry;
+mod values;
+#[cfg(feature = "watch")]
+pub mod watch;
+#[cfg(feature = "yaml")]
+mod yaml;
 
 // Re-export commonly used types from models
-pub use models::{ConfigError, EnvConfig, LoadingParam};
+pub use models::{ConfigError, EnvConfig, ErrorDetails, KeyMapping, LoadingParam};
 
-// Re-export the new loading function
-pub use loading::load_config_with_param;
+// Re-export the file format type
+pub use format::Format;
+
+// Re-export the error diagnostic type
+pub use diagnostic::Diagnostic;
+
+// Re-export the message locale type
+pub use i18n::Locale;
+
+// Re-export merge strategy types
+pub use merge::{MergeStrategies, MergeStrategy};
+
+// Re-export the new loading functions and the load report
+pub use loading::{load_config_with_param, load_config_with_report};
+pub use report::{LoadReport, LoadWarning, SourceKind, SourceReport};
+
+// Re-export the reload handle types
+pub use reload::{ConfigHandle, ConfigHandleBuilder, ConfigSnapshot, ReloadFailure};
+pub use subscription::{ConfigChange, KeyChange, SubscriptionId};
+
+// Re-export the async loading API and the change stream
+#[cfg(feature = "tokio")]
+pub use async_loading::{
+    load_config_with_param_async, load_config_with_sources_async, AsyncSource, CommandSource,
+};
+#[cfg(feature = "tokio")]
+pub use subscription::ChangeStream;
+
+// Re-export the SIGHUP listener
+#[cfg(all(unix, feature = "signal"))]
+pub use signal::SighupListener;
+
+// Re-export the configuration file watcher
+#[cfg(feature = "watch")]
+pub use watch::FileWatcher;
 
 // Keep backward compatibility with existing functions
 pub use config::{load_config, load_config_from_file, load_named_config};
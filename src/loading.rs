@@ -1,13 +1,25 @@
+use crate::diagnostic::{Diagnostic, SourceFile};
+use crate::environment;
+use crate::fields::KnownFields;
+use crate::format::{sniff_format, Format, AMBIGUOUS_EXTENSIONS};
+use crate::i18n::{self, Locale, Warning};
+use crate::merge::{self, Layer};
 use crate::models::{ConfigError, EnvConfig, LoadingParam};
-use config::{Config, File, FileFormat};
+use crate::report::{self, LoadReport, SourceKind, SourceReport};
+use crate::show_settings::{Provenance, SettingsDestination, SettingsFormat, ShowSettings};
+use crate::telemetry;
+use config::{Value, ValueKind};
 use serde::Deserialize;
 use std::env;
+use std::fs;
 use std::path::Path;
+use std::time::Instant;
 
 /// Loads configuration using the specified loading parameters.
 ///
 /// This function supports loading from both configuration files and environment variables.
-/// Environment variables have higher priority than configuration files.
+/// Environment variables have higher priority than configuration files. When several env
+/// prefixes are given, earlier prefixes have higher priority than later ones.
 ///
 /// # Arguments
 /// * `param` - The loading parameters specifying where to load configuration from
@@ -20,51 +32,187 @@ use std::path::Path;
 /// * Always logs the loading parameters at info level
 /// * If env_prefix is set and SHOW_SETTINGS=true, logs the loaded configuration at info level
 pub fn load_config_with_param<T>(param: &LoadingParam) -> Result<T, ConfigError>
+where
+    T: for<'de> Deserialize<'de> + serde::Serialize,
+{
+    load_with_layers(param, Vec::new(), false, Instant::now()).map(|(config, _)| config)
+}
+
+/// Loads configuration like `load_config_with_param`, also returning a `LoadReport`
+///
+/// The report lists the sources in order of increasing priority, the warnings that were
+/// logged, the keys the target type ignores and the time taken.
+///
+/// With the `tracing` feature, the load runs in a `dumbo_config.load` span, each source in a
+/// `dumbo_config.source` child span, and events carry the source kind, path, prefix and key count.
+pub fn load_config_with_report<T>(param: &LoadingParam) -> Result<(T, LoadReport), ConfigError>
+where
+    T: for<'de> Deserialize<'de> + serde::Serialize,
+{
+    load_with_layers(param, Vec::new(), true, Instant::now())
+}
+
+/// Runs the loading pipeline with layers already loaded, e.g. by async sources
+///
+/// The given layers sit between the file and the env prefixes, in order of increasing priority.
+/// The keys the target type ignores are only looked for when `report_ignored_keys` is set.
+/// Errors are rendered in the locale of the parameters, whichever step raised them.
+pub(crate) fn load_with_layers<T>(
+    param: &LoadingParam,
+    source_layers: Vec<Layer>,
+    report_ignored_keys: bool,
+    started: Instant,
+) -> Result<(T, LoadReport), ConfigError>
+where
+    T: for<'de> Deserialize<'de> + serde::Serialize,
+{
+    let _span = telemetry::load_span(param);
+    let locale = i18n::resolve(param.locale);
+    let result = load(param, locale, source_layers, report_ignored_keys, started)
+        .map_err(|err| err.with_locale(locale));
+    match &result {
+        Ok((_, report)) => telemetry::load_finished(report),
+        Err(err) => telemetry::load_failed(err),
+    }
+    result
+}
+
+fn load<T>(
+    param: &LoadingParam,
+    locale: Locale,
+    source_layers: Vec<Layer>,
+    report_ignored_keys: bool,
+    started: Instant,
+) -> Result<(T, LoadReport), ConfigError>
 where
     T: for<'de> Deserialize<'de> + serde::Serialize,
 {
     // Log the loading parameters
     log_loading_params(param);
+    let mut report = LoadReport::new(locale);
 
     // Validate loading parameters
     validate_loading_params(param)?;
+    let show_settings = should_show_settings(param)?;
 
-    // Build configuration sources
-    let mut config_builder = Config::builder();
+    // Load each source into its own layer, in order of increasing priority
+    let mut layers = Vec::new();
 
     // Add file source if specified
+    let profile = match param.file {
+        Some(_) => param
+            .profile
+            .map(str::to_string)
+            .or_else(crate::config::active_profile),
+        None => None,
+    };
+    let mut source_file = None;
     if let Some(file_path) = param.file {
-        config_builder = add_file_source(config_builder, file_path)?;
+        let (format, file_layers) = load_file_layers(
+            file_path,
+            param.file_format,
+            profile.as_deref(),
+            &mut report,
+        )?;
+        layers.extend(file_layers);
+        source_file = Some(SourceFile {
+            path: file_path,
+            format,
+            profile: profile.as_deref(),
+        });
+    }
+    let file_layers = layers.len();
+
+    // Add the layers of other sources
+    for layer in source_layers {
+        report.add_source(SourceReport {
+            name: layer.name.clone(),
+            kind: SourceKind::Async,
+            path: None,
+            format: None,
+            keys: report::count_values(&layer.values),
+        });
+        layers.push(layer);
     }
 
-    // Add environment variable source if specified
-    if let Some(env_config) = &param.env_prefix {
-        config_builder = add_env_source(config_builder, env_config)?;
+    // Probe the fields of the target type only when the report or the key mapping needs them
+    let known_fields = if report_ignored_keys
+        || param
+            .env_prefix
+            .iter()
+            .any(|c| c.key_mapping.uses_known_fields())
+    {
+        KnownFields::of::<T>()
+    } else {
+        KnownFields::default()
+    };
+
+    // Add environment variable sources if specified, lowest priority first
+    let replacement = param.env_prefix.iter().find(|c| !c.deprecated);
+    for env_config in param.env_prefix.iter().rev() {
+        if let Some(layer) = load_env_layer(env_config, replacement, &known_fields, &mut report)? {
+            layers.push(layer);
+        }
     }
 
-    // Build the configuration
-    let config = config_builder.build()?;
+    // Merge the layers, honouring the declared merge strategies
+    let strategies = merge::collect_strategies(&mut layers, &param.merge_strategies)?;
+    // Keep the layers for the diff format of SHOW_SETTINGS, which lists the value of each
+    let shown_layers = match &show_settings {
+        Some(settings) if settings.format == SettingsFormat::Diff => layers.clone(),
+        _ => Vec::new(),
+    };
+    let merged = merge::merge_layers(layers, &strategies);
+    if report_ignored_keys {
+        let control_vars: Vec<String> = param.env_prefix.iter().map(show_settings_name).collect();
+        report.record_ignored_keys(&merged, &known_fields, &control_vars);
+    }
 
     // Try to deserialize into the target type
-    let result: T = config.try_deserialize()?;
+    let result: T = Value::new(None, ValueKind::Table(merged))
+        .try_deserialize()
+        .map_err(|err| match Diagnostic::deserialize(err, source_file) {
+            Ok(diagnostic) => ConfigError::Deserialize(Box::new(diagnostic)),
+            Err(err) => ConfigError::from(err),
+        })?;
 
-    // Check if we should show settings
-    if should_show_settings(param) {
-        log_loaded_config(&result);
+    // Show settings if requested
+    if let Some(settings) = &show_settings {
+        let (base, overrides) = shown_layers.split_at(file_layers.min(shown_layers.len()));
+        // The documents of a multi-document file form a single base
+        let base = match base {
+            [] => None,
+            [layer] => Some(layer.clone()),
+            documents => Some(Layer::new(
+                param
+                    .file
+                    .map(|file| file.display().to_string())
+                    .unwrap_or_default(),
+                merge::merge_layers(documents.to_vec(), &strategies),
+            )),
+        };
+        let layers = Provenance {
+            base: base.as_ref(),
+            overrides,
+        };
+        log_loaded_config(&result, settings, &layers, &mut report);
     }
 
-    Ok(result)
+    report.duration = started.elapsed();
+    Ok((result, report))
 }
 
 /// Validates the loading parameters and returns appropriate errors
 pub fn validate_loading_params(param: &LoadingParam) -> Result<(), ConfigError> {
-    // Check if both sources are None
-    if param.file.is_none() && param.env_prefix.is_none() {
-        return Err(ConfigError::InvalidLoadingParam);
+    // Check if both sources are missing
+    if param.file.is_none() && param.env_prefix.is_empty() {
+        return Err(ConfigError::InvalidLoadingParam {
+            locale: Locale::from_env(),
+        });
     }
 
-    // Validate environment configuration if present
-    if let Some(env_config) = &param.env_prefix {
+    // Validate environment configurations if present
+    for env_config in &param.env_prefix {
         validate_env_config(env_config)?;
     }
 
@@ -78,135 +226,288 @@ fn validate_env_config(env_config: &EnvConfig) -> Result<(), ConfigError> {
         return Err(ConfigError::InvalidEnvConfig {
             prefix: env_config.name.clone(),
             separator: separator.to_string(),
+            locale: Locale::from_env(),
         });
     }
     Ok(())
 }
 
-/// Adds file source to the configuration builder
-fn add_file_source(
-    config_builder: config::ConfigBuilder<config::builder::DefaultState>,
+/// Loads the configuration file into a layer per document, along with its format
+///
+/// For multi-document YAML, the untagged documents and those of the given profile each
+/// get a layer, named after the file and the position of the document among them.
+fn load_file_layers(
     file_path: &Path,
-) -> Result<config::ConfigBuilder<config::builder::DefaultState>, ConfigError> {
+    file_format: Option<Format>,
+    profile: Option<&str>,
+    report: &mut LoadReport,
+) -> Result<(Format, Vec<Layer>), ConfigError> {
+    let _span = telemetry::file_span(file_path);
+
     // Check if file exists
     if !file_path.exists() {
-        return Err(ConfigError::FileNotFound(file_path.to_path_buf()));
+        return Err(ConfigError::FileNotFound {
+            path: file_path.to_path_buf(),
+            locale: Locale::from_env(),
+        });
     }
 
-    // Determine file format from extension
-    let format = get_file_format(file_path);
+    // Determine file format from the parameter, the extension or the content
+    let format = get_file_format(file_path, file_format)?;
 
-    // Add source and return new builder
-    Ok(config_builder.add_source(File::from(file_path).format(format)))
+    // Parse the file and collect its values
+    let origin = file_path.display().to_string();
+    let documents = format.parse_file(file_path, &origin, profile)?;
+    report.add_source(SourceReport {
+        name: origin.clone(),
+        kind: SourceKind::File,
+        path: Some(file_path.to_path_buf()),
+        format: Some(format),
+        keys: documents.iter().map(report::count_values).sum(),
+    });
+    let multi_document = documents.len() > 1;
+    let layers = documents
+        .into_iter()
+        .enumerate()
+        .map(|(index, values)| match multi_document {
+            true => Layer::new(format!("{}#{}", origin, index + 1), values),
+            false => Layer::new(origin.clone(), values),
+        })
+        .collect();
+    Ok((format, layers))
 }
 
-/// Gets the file format based on file extension
-fn get_file_format(file_path: &Path) -> FileFormat {
-    match file_path.extension().and_then(|ext| ext.to_str()) {
-        Some("json") => FileFormat::Json,
-        Some("yaml") | Some("yml") => FileFormat::Yaml,
-        Some("toml") => FileFormat::Toml,
-        Some("ini") => FileFormat::Ini,
-        // Remove Properties as it's not supported in current config version
-        _ => FileFormat::Yaml, // Default to YAML
+/// Gets the file format of a configuration file
+///
+/// An explicit format always wins. Otherwise the extension decides; for ambiguous
+/// extensions (`.conf`, `.cfg`, `.config`, `.txt` or none) the content is sniffed.
+/// Unknown extensions are reported as an error rather than guessed, and formats whose
+/// cargo feature is disabled are reported as such.
+fn get_file_format(file_path: &Path, explicit: Option<Format>) -> Result<Format, ConfigError> {
+    if let Some(format) = explicit {
+        return enabled(format, file_path);
+    }
+
+    let extension = file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    let unknown_format = || ConfigError::UnknownFileFormat {
+        path: file_path.to_path_buf(),
+        extension: extension.clone(),
+        locale: Locale::from_env(),
+    };
+
+    match extension.as_deref() {
+        Some(ext) if !AMBIGUOUS_EXTENSIONS.contains(&ext) => {
+            let format = Format::from_extension(ext).ok_or_else(unknown_format)?;
+            enabled(format, file_path)
+        }
+        _ => {
+            let content = fs::read_to_string(file_path).unwrap_or_default();
+            let format = sniff_format(&content).ok_or_else(unknown_format)?;
+            log::info!(
+                "Detected {} format for configuration file {:?} from its content",
+                format,
+                file_path
+            );
+            Ok(format)
+        }
+    }
+}
+
+/// Checks that the cargo feature of the format is enabled
+fn enabled(format: Format, file_path: &Path) -> Result<Format, ConfigError> {
+    if format.is_enabled() {
+        Ok(format)
+    } else {
+        Err(format.not_enabled(file_path))
     }
 }
 
-/// Adds environment variable source to the configuration builder if environment variables exist
+/// Loads environment variables into their own layer if environment variables exist
 ///
-/// Returns the same builder if no environment variables with the specified prefix exist (logs a warning).
-/// Otherwise, returns a new builder with the environment source added.
-fn add_env_source(
-    config_builder: config::ConfigBuilder<config::builder::DefaultState>,
+/// Returns `None` if no environment variables with the specified prefix exist (logs a warning),
+/// or `ConfigError::MissingEnvVars` if the prefix is required.
+/// For a deprecated prefix, logs a warning naming the replacement of each variable in use.
+fn load_env_layer(
     env_config: &EnvConfig,
-) -> Result<config::ConfigBuilder<config::builder::DefaultState>, ConfigError> {
+    replacement: Option<&EnvConfig>,
+    known_fields: &KnownFields,
+    report: &mut LoadReport,
+) -> Result<Option<Layer>, ConfigError> {
     let prefix = &env_config.name;
-    let separator = env_config.get_separator();
+    let name = format!("env:{}", prefix);
+    let _span = telemetry::env_span(prefix);
 
-    // Check if any environment variables exist with this prefix
+    // Check if any environment variables exist with this prefix or as an alias
+    let separator = env_config.get_separator();
     let env_vars_with_prefix: Vec<String> = env::vars()
-        .filter(|(key, _)| key.starts_with(prefix))
+        .filter(|(key, _)| {
+            environment::strip_env_prefix(key, prefix, separator).is_some()
+                || env_config.aliases.contains_key(key)
+        })
         .map(|(key, _)| key)
         .collect();
 
-    // If no environment variables found with this prefix, log and return the original builder
+    // If no environment variables found with this prefix, fail when required, or log and skip the layer
     if env_vars_with_prefix.is_empty() {
-        log::warn!("No environment variables found with prefix: '{}'. Skipping environment variable loading.", prefix);
-        return Ok(config_builder);
+        if env_config.required {
+            return Err(ConfigError::MissingEnvVars {
+                prefix: prefix.clone(),
+                similar: environment::similar_names(
+                    prefix,
+                    separator,
+                    env::vars().map(|(key, _)| key),
+                ),
+                locale: Locale::from_env(),
+            });
+        }
+        report.warn(Warning::NoEnvVars { prefix });
+        report.add_source(SourceReport {
+            name,
+            kind: SourceKind::Env,
+            path: None,
+            format: None,
+            keys: 0,
+        });
+        return Ok(None);
+    }
+
+    // Warn about variables still using a deprecated prefix
+    if env_config.deprecated {
+        for name in &env_vars_with_prefix {
+            let new_name =
+                replacement.and_then(|r| environment::replacement_name(name, env_config, r));
+            report.warn(Warning::DeprecatedPrefix {
+                name,
+                prefix,
+                replacement: new_name.as_deref(),
+            });
+        }
     }
 
-    // Add source and return new builder
-    Ok(config_builder.add_source(
-        config::Environment::with_prefix(prefix)
-            .separator(separator)
-            .try_parsing(true),
-    ))
+    // Collect the environment variables as nested values
+    let values = environment::collect_env_values(env_config, known_fields, report)?;
+    report.add_source(SourceReport {
+        name: name.clone(),
+        kind: SourceKind::Env,
+        path: None,
+        format: None,
+        keys: report::count_values(&values),
+    });
+    Ok(Some(Layer::new(name, values)))
 }
 
-/// Checks if SHOW_SETTINGS environment variable is set to true
-fn should_show_settings(param: &LoadingParam) -> bool {
-    if let Some(env_config) = &param.env_prefix {
-        let env_full_name = format!(
-            "{}{}SHOW_SETTINGS",
-            &env_config.name,
-            &env_config.get_separator()
-        );
+/// Gets what the SHOW_SETTINGS environment variable asks for, if anything
+///
+/// With several env prefixes, the first prefix (by priority) that sets SHOW_SETTINGS decides.
+/// Values that are neither a boolean nor a format count as false, or are reported as
+/// `ConfigError::ShowSettingsParseError` when the deciding prefix is required.
+fn should_show_settings(param: &LoadingParam) -> Result<Option<ShowSettings>, ConfigError> {
+    let env_config = param
+        .env_prefix
+        .iter()
+        .find(|env_config| env::var_os(show_settings_name(env_config)).is_some());
+    if let Some(env_config) = env_config.or_else(|| param.env_prefix.first()) {
+        let env_full_name = show_settings_name(env_config);
         match env::var(&env_full_name) {
             Ok(value) => {
-                let lower_value = value.to_lowercase();
-                let result = lower_value == "true"
-                    || lower_value == "1"
-                    || lower_value == "yes"
-                    || lower_value == "on";
-                log::info!("{} is set, return {}", &env_full_name, result);
-                result
+                let settings = match ShowSettings::parse(&value, env_config) {
+                    Ok(settings) => settings,
+                    Err(err) if env_config.required => return Err(err),
+                    Err(_) => None,
+                };
+                log::info!("{} is set, return {}", &env_full_name, settings.is_some());
+                Ok(settings)
             }
             Err(_) => {
-                log::warn!("{} not set, return false", &env_full_name);
-                false
+                log::debug!("{} not set, return false", &env_full_name);
+                Ok(None)
             }
         }
     } else {
-        false
+        Ok(None)
     }
 }
 
+/// Gets the full name of the SHOW_SETTINGS environment variable for the given prefix
+fn show_settings_name(env_config: &EnvConfig) -> String {
+    format!(
+        "{}{}SHOW_SETTINGS",
+        &env_config.name,
+        &env_config.get_separator()
+    )
+}
+
 /// Logs the loading parameters
 fn log_loading_params(param: &LoadingParam) {
     if let Some(file_path) = param.file {
-        log::info!("Loading configuration from file: {:?}", file_path);
+        match param.file_format {
+            Some(format) => log::info!(
+                "Loading configuration from file: {:?} as {}",
+                file_path,
+                format
+            ),
+            None => log::info!("Loading configuration from file: {:?}", file_path),
+        }
     }
 
-    if let Some(env_config) = &param.env_prefix {
+    for env_config in &param.env_prefix {
         log::info!(
-            "Loading configuration from environment variables with prefix: '{}' and separator: '{}'",
+            "Loading configuration from environment variables with prefix: '{}' and separator: '{}'{}",
             env_config.name,
-            env_config.get_separator()
+            env_config.get_separator(),
+            if env_config.deprecated { " (deprecated)" } else { "" }
         );
     }
 }
 
-/// Logs that configuration was loaded (when SHOW_SETTINGS is enabled)
-fn log_loaded_config<T>(config: &T)
-where
+/// Shows the loaded configuration in the format and at the destination SHOW_SETTINGS asks for
+///
+/// The diff format compares the configuration with the given layers.
+fn log_loaded_config<T>(
+    config: &T,
+    settings: &ShowSettings,
+    layers: &Provenance,
+    report: &mut LoadReport,
+) where
     T: serde::Serialize,
 {
-    match serde_json::to_string_pretty(config) {
-        Ok(serialized) => {
-            log::info!(
-                "Configuration loaded successfully (SHOW_SETTINGS enabled):\n{}",
-                serialized
-            );
-        }
+    let rendered = match settings.render(config, layers) {
+        Ok(rendered) => rendered,
         Err(e) => {
-            log::warn!("Failed to serialize configuration for logging: {}", e);
+            report.warn(Warning::SerializeFailed { error: &e });
             log::info!("Configuration loaded successfully (SHOW_SETTINGS enabled)");
+            return;
+        }
+    };
+
+    match &settings.destination {
+        SettingsDestination::Log => log::info!(
+            "Configuration loaded successfully (SHOW_SETTINGS enabled):\n{}",
+            rendered
+        ),
+        SettingsDestination::Stderr => {
+            eprintln!("{}", rendered);
+            log::info!(
+                "Configuration loaded successfully (SHOW_SETTINGS enabled), written to stderr as {}",
+                settings.format
+            );
         }
+        SettingsDestination::File(path) => match fs::write(path, format!("{}\n", rendered)) {
+            Ok(()) => log::info!(
+                "Configuration loaded successfully (SHOW_SETTINGS enabled), written to {:?} as {}",
+                path,
+                settings.format
+            ),
+            Err(e) => report.warn(Warning::ShowSettingsWriteFailed { path, error: &e }),
+        },
     }
 }
 
 // Expose should_show_settings for testing purposes
 #[cfg(test)]
 pub fn test_should_show_settings(param: &LoadingParam) -> bool {
-    should_show_settings(param)
+    should_show_settings(param).unwrap().is_some()
 }
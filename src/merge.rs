@@ -0,0 +1,253 @@
+use crate::format::Documents;
+use crate::i18n::Locale;
+use crate::models::ConfigError;
+use config::{Map, Value, ValueKind};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Reserved top-level key a configuration file may use to declare merge strategies
+pub const MERGE_DIRECTIVE_KEY: &str = "_merge";
+
+/// Strategy used when a later source provides a value for a key an earlier source already set
+///
+/// Tables are always merged deeply; the strategy only changes how arrays are combined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// The later value replaces the earlier one (default)
+    Replace,
+    /// Items of the later array are added after the earlier items
+    Append,
+    /// Items of the later array are added before the earlier items
+    Prepend,
+    /// Arrays of tables are merged item by item, matching items on the given field
+    MergeByKey(String),
+}
+
+/// Merge strategies keyed by dotted key path, e.g. `http.allowed_origins`
+pub type MergeStrategies = HashMap<String, MergeStrategy>;
+
+impl MergeStrategy {
+    /// Parses a strategy as written in a `_merge` table:
+    /// `replace`, `append`, `prepend` or `merge_by_key:<field>`
+    pub fn parse(value: &str) -> Option<Self> {
+        let (name, field) = match value.split_once(':') {
+            Some((name, field)) => (name.trim(), Some(field.trim())),
+            None => (value.trim(), None),
+        };
+        match (name.to_lowercase().as_str(), field) {
+            ("replace", None) => Some(MergeStrategy::Replace),
+            ("append", None) => Some(MergeStrategy::Append),
+            ("prepend", None) => Some(MergeStrategy::Prepend),
+            ("merge_by_key", Some(field)) if !field.is_empty() => {
+                Some(MergeStrategy::MergeByKey(field.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeStrategy::Replace => write!(f, "replace"),
+            MergeStrategy::Append => write!(f, "append"),
+            MergeStrategy::Prepend => write!(f, "prepend"),
+            MergeStrategy::MergeByKey(field) => write!(f, "merge_by_key:{}", field),
+        }
+    }
+}
+
+/// A set of configuration values loaded from a single source
+#[derive(Debug, Clone)]
+pub(crate) struct Layer {
+    pub name: String,
+    pub values: Map<String, Value>,
+}
+
+impl Layer {
+    pub fn new(name: String, values: Map<String, Value>) -> Self {
+        Self { name, values }
+    }
+}
+
+/// Removes the `_merge` directive table from each layer and combines it with the
+/// strategies given in the loading parameters. Parameters win over file directives.
+pub(crate) fn collect_strategies(
+    layers: &mut [Layer],
+    declared: &MergeStrategies,
+) -> Result<MergeStrategies, ConfigError> {
+    let mut strategies = MergeStrategies::new();
+
+    for layer in layers.iter_mut() {
+        if let Some(directives) = layer.values.remove(MERGE_DIRECTIVE_KEY) {
+            collect_directives(&mut strategies, String::new(), directives)?;
+        }
+    }
+
+    strategies.extend(declared.iter().map(|(k, v)| (k.clone(), v.clone())));
+    Ok(strategies)
+}
+
+/// Reads a `_merge` table, allowing both dotted keys and nested tables
+fn collect_directives(
+    strategies: &mut MergeStrategies,
+    prefix: String,
+    directives: Value,
+) -> Result<(), ConfigError> {
+    match directives.kind {
+        ValueKind::Table(table) => {
+            for (key, value) in table {
+                collect_directives(strategies, join_path(&prefix, &key), value)?;
+            }
+            Ok(())
+        }
+        ValueKind::String(ref raw) => match MergeStrategy::parse(raw) {
+            Some(strategy) => {
+                strategies.insert(prefix, strategy);
+                Ok(())
+            }
+            None => Err(ConfigError::InvalidMergeStrategy {
+                key: prefix,
+                value: raw.clone(),
+                locale: Locale::from_env(),
+            }),
+        },
+        _ => Err(ConfigError::InvalidMergeStrategy {
+            key: prefix,
+            value: directives.to_string(),
+            locale: Locale::from_env(),
+        }),
+    }
+}
+
+/// Merges the documents of a file in order, like layers, honouring their `_merge` directives
+pub(crate) fn merge_documents(
+    documents: Documents,
+    origin: &str,
+) -> Result<Map<String, Value>, ConfigError> {
+    let mut layers: Vec<Layer> = documents
+        .into_iter()
+        .map(|values| Layer::new(origin.to_string(), values))
+        .collect();
+    let strategies = collect_strategies(&mut layers, &MergeStrategies::new())?;
+    Ok(merge_layers(layers, &strategies))
+}
+
+/// Merges the layers in order; later layers take precedence over earlier ones
+pub(crate) fn merge_layers(layers: Vec<Layer>, strategies: &MergeStrategies) -> Map<String, Value> {
+    let mut merged = Map::new();
+    for layer in layers {
+        log::debug!("Merging configuration layer: {}", layer.name);
+        merge_tables(&mut merged, layer.values, "", strategies);
+    }
+    merged
+}
+
+fn merge_tables(
+    base: &mut Map<String, Value>,
+    incoming: Map<String, Value>,
+    prefix: &str,
+    strategies: &MergeStrategies,
+) {
+    for (key, value) in incoming {
+        let path = join_path(prefix, &key);
+
+        // A null value is a tombstone: it deletes whatever earlier layers set
+        if matches!(value.kind, ValueKind::Nil) {
+            base.remove(&key);
+            continue;
+        }
+
+        match base.remove(&key) {
+            Some(existing) => {
+                base.insert(key, merge_values(existing, value, &path, strategies));
+            }
+            None => {
+                base.insert(key, strip_tombstones(value));
+            }
+        }
+    }
+}
+
+fn merge_values(
+    existing: Value,
+    incoming: Value,
+    path: &str,
+    strategies: &MergeStrategies,
+) -> Value {
+    let origin = incoming.origin().map(str::to_string);
+    match (existing.kind, incoming.kind) {
+        (ValueKind::Table(mut base), ValueKind::Table(table)) => {
+            merge_tables(&mut base, table, path, strategies);
+            Value::new(origin.as_ref(), ValueKind::Table(base))
+        }
+        (ValueKind::Array(base), ValueKind::Array(items)) => {
+            let items: Vec<Value> = items.into_iter().map(strip_tombstones).collect();
+            let merged = match strategies.get(path) {
+                Some(MergeStrategy::Append) => base.into_iter().chain(items).collect(),
+                Some(MergeStrategy::Prepend) => items.into_iter().chain(base).collect(),
+                Some(MergeStrategy::MergeByKey(field)) => {
+                    merge_arrays_by_key(base, items, field, path, strategies)
+                }
+                Some(MergeStrategy::Replace) | None => items,
+            };
+            Value::new(origin.as_ref(), ValueKind::Array(merged))
+        }
+        (_, kind) => strip_tombstones(Value::new(origin.as_ref(), kind)),
+    }
+}
+
+fn merge_arrays_by_key(
+    mut base: Vec<Value>,
+    items: Vec<Value>,
+    field: &str,
+    path: &str,
+    strategies: &MergeStrategies,
+) -> Vec<Value> {
+    for item in items {
+        let position = item_key(&item, field).and_then(|wanted| {
+            base.iter()
+                .position(|b| item_key(b, field).as_ref() == Some(&wanted))
+        });
+        match position {
+            Some(index) => {
+                let existing = base.remove(index);
+                base.insert(index, merge_values(existing, item, path, strategies));
+            }
+            None => base.push(item),
+        }
+    }
+    base
+}
+
+fn item_key(item: &Value, field: &str) -> Option<String> {
+    match &item.kind {
+        ValueKind::Table(table) => table.get(field).map(|v| v.to_string()),
+        _ => None,
+    }
+}
+
+/// Tombstones only make sense against an earlier layer, so nulls nested inside
+/// newly added tables are dropped
+fn strip_tombstones(value: Value) -> Value {
+    let origin = value.origin().map(str::to_string);
+    match value.kind {
+        ValueKind::Table(table) => {
+            let table: Map<String, Value> = table
+                .into_iter()
+                .filter(|(_, v)| !matches!(v.kind, ValueKind::Nil))
+                .map(|(k, v)| (k, strip_tombstones(v)))
+                .collect();
+            Value::new(origin.as_ref(), ValueKind::Table(table))
+        }
+        kind => Value::new(origin.as_ref(), kind),
+    }
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
@@ -1,89 +1,592 @@
+use crate::diagnostic::Diagnostic;
+use crate::format::Format;
+use crate::i18n::{self, Locale};
+use crate::merge::MergeStrategies;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 const DEFAULT_SEPERATOR: &str = "__";
 
+/// Strategy for mapping the segments of environment variable names to configuration keys
+#[derive(Clone, Default)]
+pub enum KeyMapping {
+    /// `MAX_CONNS` maps to `max_conns` (default)
+    #[default]
+    Lowercase,
+    /// `MAX_CONNS` maps to `MAX_CONNS`
+    Preserve,
+    /// `MAX_CONNS` maps to `maxConns`
+    CamelCase,
+    /// `MAX_CONNS` maps to `max-conns`
+    KebabCase,
+    /// `MAX_CONNS` maps to the known field matching it regardless of case, `_` and `-`,
+    /// e.g. `maxConns`; unknown segments fall back to lowercase
+    CaseInsensitive,
+    /// Maps each segment with the given function
+    Custom(Arc<dyn Fn(&str) -> String + Send + Sync>),
+}
+
+impl KeyMapping {
+    /// Maps a single segment of an environment variable name, given the fields known at that level
+    pub fn map_segment(&self, segment: &str, known_fields: &[&str]) -> String {
+        match self {
+            KeyMapping::Lowercase => segment.to_lowercase(),
+            KeyMapping::Preserve => segment.to_string(),
+            KeyMapping::CamelCase => {
+                let mut words = segment.split('_').filter(|word| !word.is_empty());
+                let mut key = words.next().unwrap_or_default().to_lowercase();
+                for word in words {
+                    let mut chars = word.chars();
+                    if let Some(first) = chars.next() {
+                        key.extend(first.to_uppercase());
+                        key.push_str(&chars.as_str().to_lowercase());
+                    }
+                }
+                key
+            }
+            KeyMapping::KebabCase => segment.to_lowercase().replace('_', "-"),
+            KeyMapping::CaseInsensitive => {
+                let normalized = normalize_key(segment);
+                known_fields
+                    .iter()
+                    .find(|field| normalize_key(field) == normalized)
+                    .map(|field| field.to_string())
+                    .unwrap_or_else(|| segment.to_lowercase())
+            }
+            KeyMapping::Custom(map) => map(segment),
+        }
+    }
+
+    /// Maps a configuration key back to a segment of an environment variable name
+    ///
+    /// e.g. `maxConns` gives `MAX_CONNS` with `CamelCase`. A custom mapping cannot be
+    /// inverted, so its keys are uppercased.
+    pub(crate) fn unmap_key(&self, key: &str) -> String {
+        match self {
+            KeyMapping::Preserve => key.to_string(),
+            KeyMapping::Lowercase | KeyMapping::Custom(_) => key.to_uppercase(),
+            KeyMapping::CamelCase | KeyMapping::KebabCase | KeyMapping::CaseInsensitive => {
+                let mut segment = String::new();
+                let mut after_word = false;
+                for c in key.chars() {
+                    if c == '-' {
+                        segment.push('_');
+                        after_word = false;
+                        continue;
+                    }
+                    if c.is_uppercase() && after_word {
+                        segment.push('_');
+                    }
+                    after_word = c.is_lowercase() || c.is_ascii_digit();
+                    segment.extend(c.to_uppercase());
+                }
+                segment
+            }
+        }
+    }
+
+    /// Checks whether segments are mapped using the fields declared by the target type
+    pub(crate) fn uses_known_fields(&self) -> bool {
+        matches!(self, KeyMapping::CaseInsensitive)
+    }
+}
+
+impl fmt::Debug for KeyMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyMapping::Lowercase => write!(f, "Lowercase"),
+            KeyMapping::Preserve => write!(f, "Preserve"),
+            KeyMapping::CamelCase => write!(f, "CamelCase"),
+            KeyMapping::KebabCase => write!(f, "KebabCase"),
+            KeyMapping::CaseInsensitive => write!(f, "CaseInsensitive"),
+            KeyMapping::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 /// Environment configuration for loading settings from environment variables
+#[derive(Clone)]
 pub struct EnvConfig {
-    pub name: String,              // Environment variable prefix
-    pub separator: Option<String>, // Environment variable separator, defaults to "_"
+    pub name: String,                     // Environment variable prefix
+    pub separator: Option<String>,        // Environment variable separator, defaults to "__"
+    pub list_separator: Option<String>,   // Splits values into lists, e.g. "," for "a,b,c"
+    pub list_keys: Vec<String>,           // Keys split by list_separator; all keys when empty
+    pub indexed_arrays: bool,             // Turns MY_APP__SERVERS__0__HOST into an array entry
+    pub json_values: bool,                // Parses values starting with '{' or '[' as JSON
+    pub key_mapping: KeyMapping,          // Maps variable name segments to configuration keys
+    pub aliases: HashMap<String, String>, // Full variable name -> dotted key path
+    pub deprecated: bool,                 // Warns about variables using this prefix
+    pub required: bool,                   // Fails when no variable uses this prefix
 }
 
 impl EnvConfig {
     /// Creates a new EnvConfig with the given name and optional separator
     pub fn new(name: String, separator: Option<String>) -> Self {
-        Self { name, separator }
+        Self {
+            name,
+            separator,
+            list_separator: None,
+            list_keys: Vec::new(),
+            indexed_arrays: false,
+            json_values: false,
+            key_mapping: KeyMapping::default(),
+            aliases: HashMap::new(),
+            deprecated: false,
+            required: false,
+        }
     }
 
-    /// Gets the separator, defaulting to "_" if not specified
+    /// Gets the separator, defaulting to "__" if not specified
     pub fn get_separator(&self) -> &str {
         self.separator.as_deref().unwrap_or(DEFAULT_SEPERATOR)
     }
+
+    /// Splits values into lists using the given separator
+    ///
+    /// When `keys` is empty every value is split; otherwise only the listed
+    /// dotted keys (e.g. `http.allowed_origins`) are.
+    pub fn with_list_separator(mut self, list_separator: &str, keys: Vec<String>) -> Self {
+        self.list_separator = Some(list_separator.to_string());
+        self.list_keys = keys;
+        self
+    }
+
+    /// Enables indexed arrays such as `MY_APP__SERVERS__0__HOST`
+    pub fn with_indexed_arrays(mut self, enabled: bool) -> Self {
+        self.indexed_arrays = enabled;
+        self
+    }
+
+    /// Enables inline JSON values such as `MY_APP__LIMITS='{"a":1}'`
+    pub fn with_json_values(mut self, enabled: bool) -> Self {
+        self.json_values = enabled;
+        self
+    }
+
+    /// Sets the strategy used to map variable name segments to configuration keys
+    pub fn with_key_mapping(mut self, key_mapping: KeyMapping) -> Self {
+        self.key_mapping = key_mapping;
+        self
+    }
+
+    /// Reads the given variable, which needs no prefix, into the given dotted key path
+    ///
+    /// e.g. `with_alias("DATABASE_URL", "db.url")`
+    pub fn with_alias(mut self, env_name: &str, key_path: &str) -> Self {
+        self.aliases
+            .insert(env_name.to_string(), key_path.to_string());
+        self
+    }
+
+    /// Marks this prefix as deprecated
+    ///
+    /// Variables using it are still loaded, with lower priority than the prefixes listed
+    /// before it, and a warning names the variable that replaces each of them.
+    pub fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    /// Marks this prefix as required
+    ///
+    /// Loading fails with `ConfigError::MissingEnvVars` when no variable uses the prefix,
+    /// and with `ConfigError::ShowSettingsParseError` when its SHOW_SETTINGS variable is
+    /// not a boolean.
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Gets the list separator that applies to the given dotted key, if any
+    pub fn list_separator_for(&self, key: &str) -> Option<&str> {
+        let list_separator = self.list_separator.as_deref()?;
+        if self.list_keys.is_empty() || self.list_keys.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+            Some(list_separator)
+        } else {
+            None
+        }
+    }
 }
 
 /// Loading parameters for configuration
 ///
 /// Note: env_prefix has higher priority than file, meaning if both are present,
-/// settings from env_prefix will override those from file.
+/// settings from env_prefix will override those from file. Env prefixes are listed in
+/// order of priority: settings from earlier prefixes override those from later ones.
+#[derive(Default)]
 pub struct LoadingParam<'a> {
-    pub file: Option<&'a Path>,        // Configuration file path
-    pub env_prefix: Option<EnvConfig>, // Environment variable configuration
+    pub file: Option<&'a Path>,            // Configuration file path
+    pub file_format: Option<Format>,       // Format of the file; detected when not set
+    pub env_prefix: Vec<EnvConfig>,        // Environment variable configurations, by priority
+    pub merge_strategies: MergeStrategies, // Per-key merge strategies, keyed by dotted path
+    pub profile: Option<&'a str>,          // Profile selecting YAML documents; defaults to $ENV
+    pub locale: Option<Locale>,            // Language of messages; defaults to $DUMBO_CONFIG_LANG
+}
+
+/// Loading parameters owning their file path and profile, for loads that outlive the caller
+pub(crate) struct OwnedLoadingParam {
+    pub(crate) file: Option<PathBuf>,
+    pub(crate) file_format: Option<Format>,
+    pub(crate) env_prefix: Vec<EnvConfig>,
+    pub(crate) merge_strategies: MergeStrategies,
+    pub(crate) profile: Option<String>,
+    pub(crate) locale: Option<Locale>,
+}
+
+impl OwnedLoadingParam {
+    /// Copies the given loading parameters
+    pub(crate) fn new(param: &LoadingParam) -> Self {
+        Self {
+            file: param.file.map(Path::to_path_buf),
+            file_format: param.file_format,
+            env_prefix: param.env_prefix.clone(),
+            merge_strategies: param.merge_strategies.clone(),
+            profile: param.profile.map(str::to_string),
+            locale: param.locale,
+        }
+    }
+
+    /// Gets the loading parameters borrowing from this copy
+    pub(crate) fn param(&self) -> LoadingParam<'_> {
+        LoadingParam {
+            file: self.file.as_deref(),
+            file_format: self.file_format,
+            env_prefix: self.env_prefix.clone(),
+            merge_strategies: self.merge_strategies.clone(),
+            profile: self.profile.as_deref(),
+            locale: self.locale,
+        }
+    }
 }
 
 /// Configuration loading errors
+///
+/// Each variant has a stable `code()` for grouping errors in log pipelines, and
+/// `details()` gives the code, message, key path, source and hint as structured data.
+/// Every variant carries the locale its messages and hints are rendered in: the locale of
+/// the loading parameters that raised it (see `LoadingParam.locale`), or of the environment
+/// for errors raised outside a load. The `*_in` methods render them in a given locale.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ConfigError {
     /// Wrapped config crate error
-    Config(config::ConfigError),
+    Config {
+        error: config::ConfigError,
+        locale: Locale,
+    },
     /// Configuration file not found
-    FileNotFound(std::path::PathBuf),
+    FileNotFound {
+        path: std::path::PathBuf,
+        locale: Locale,
+    },
     /// SHOW_SETTINGS environment variable cannot be parsed as boolean
-    ShowSettingsParseError(String),
-    /// Invalid loading parameter: both file and env_prefix are None
-    InvalidLoadingParam,
+    ShowSettingsParseError { value: String, locale: Locale },
+    /// Invalid loading parameter: neither file nor env_prefix is set
+    InvalidLoadingParam { locale: Locale },
     /// Invalid environment configuration: env prefix contains separator
-    InvalidEnvConfig { prefix: String, separator: String },
+    InvalidEnvConfig {
+        prefix: String,
+        separator: String,
+        locale: Locale,
+    },
+    /// Invalid merge strategy declared in a `_merge` table
+    InvalidMergeStrategy {
+        key: String,
+        value: String,
+        locale: Locale,
+    },
+    /// Environment variable value cannot be parsed
+    InvalidEnvValue {
+        name: String,
+        reason: String,
+        locale: Locale,
+    },
+    /// No environment variable uses a required prefix; `similar` lists the closest names found
+    MissingEnvVars {
+        prefix: String,
+        similar: Vec<String>,
+        locale: Locale,
+    },
+    /// Format of the configuration file cannot be determined
+    UnknownFileFormat {
+        path: std::path::PathBuf,
+        extension: Option<String>,
+        locale: Locale,
+    },
+    /// Configuration file cannot be parsed; the diagnostic carries the locale
+    Parse(Box<Diagnostic>),
+    /// Configuration value is missing or does not match the target type; the diagnostic
+    /// carries the locale
+    Deserialize(Box<Diagnostic>),
+    /// Configuration file format whose cargo feature is disabled
+    FormatNotEnabled {
+        path: std::path::PathBuf,
+        format: Format,
+        locale: Locale,
+    },
+    /// Loaded configuration rejected by the validator of a `ConfigHandle`
+    ValidationFailed { reason: String, locale: Locale },
+    /// No snapshot with the given version in the history of a `ConfigHandle`
+    SnapshotNotFound { version: u64, locale: Locale },
+    /// Async source failed to load, or did not return a JSON object
+    SourceFailed {
+        name: String,
+        reason: String,
+        locale: Locale,
+    },
+    /// Blocking part of an async load was cancelled, e.g. as the runtime shuts down
+    LoadCancelled { locale: Locale },
 }
 
-impl fmt::Display for ConfigError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// Structured data of a `ConfigError`, e.g. for JSON logs
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorDetails {
+    pub code: &'static str, // Stable error code, e.g. `dumbo_config::file_not_found`
+    pub message: String,    // What went wrong, without the hint
+    pub key_path: Option<String>, // Dotted key path concerned, if any
+    pub source: Option<String>, // File path, environment variable or prefix concerned, if any
+    pub hint: String,       // How to fix it
+}
+
+impl ConfigError {
+    /// Gets the location and context of parse and deserialize errors
+    pub fn diagnostic(&self) -> Option<&Diagnostic> {
         match self {
-            ConfigError::Config(err) => write!(f, "Config error: {}", err),
-            ConfigError::FileNotFound(path) => {
-                write!(f, "Configuration file not found: {:?}", path)
+            ConfigError::Parse(diagnostic) | ConfigError::Deserialize(diagnostic) => {
+                Some(diagnostic)
             }
-            ConfigError::ShowSettingsParseError(value) => {
-                write!(
-                    f,
-                    "Cannot parse SHOW_SETTINGS environment variable '{}' as boolean",
-                    value
-                )
+            _ => None,
+        }
+    }
+
+    /// Gets the stable code of the error, which does not change with the message wording
+    pub fn code(&self) -> &'static str {
+        match self {
+            ConfigError::Config { .. } => "dumbo_config::config",
+            ConfigError::FileNotFound { .. } => "dumbo_config::file_not_found",
+            ConfigError::ShowSettingsParseError { .. } => "dumbo_config::show_settings_parse",
+            ConfigError::InvalidLoadingParam { .. } => "dumbo_config::invalid_loading_param",
+            ConfigError::InvalidEnvConfig { .. } => "dumbo_config::invalid_env_config",
+            ConfigError::InvalidMergeStrategy { .. } => "dumbo_config::invalid_merge_strategy",
+            ConfigError::InvalidEnvValue { .. } => "dumbo_config::invalid_env_value",
+            ConfigError::MissingEnvVars { .. } => "dumbo_config::missing_env_vars",
+            ConfigError::UnknownFileFormat { .. } => "dumbo_config::unknown_file_format",
+            ConfigError::Parse(_) => "dumbo_config::parse",
+            ConfigError::Deserialize(_) => "dumbo_config::deserialize",
+            ConfigError::FormatNotEnabled { .. } => "dumbo_config::format_not_enabled",
+            ConfigError::ValidationFailed { .. } => "dumbo_config::validation_failed",
+            ConfigError::SnapshotNotFound { .. } => "dumbo_config::snapshot_not_found",
+            ConfigError::SourceFailed { .. } => "dumbo_config::source_failed",
+            ConfigError::LoadCancelled { .. } => "dumbo_config::load_cancelled",
+        }
+    }
+
+    /// Gets the dotted key path the error is about, if any
+    pub fn key_path(&self) -> Option<String> {
+        match self {
+            ConfigError::Config { error, .. } => config_error_key(error),
+            ConfigError::InvalidMergeStrategy { key, .. } => Some(key.clone()),
+            ConfigError::Parse(diagnostic) | ConfigError::Deserialize(diagnostic) => {
+                diagnostic.key_path.clone()
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the file path, environment variable or prefix that caused the error, if any
+    pub fn source_name(&self) -> Option<String> {
+        match self {
+            ConfigError::Config { error, .. } => config_error_origin(error),
+            ConfigError::FileNotFound { path, .. }
+            | ConfigError::UnknownFileFormat { path, .. }
+            | ConfigError::FormatNotEnabled { path, .. } => Some(path.display().to_string()),
+            ConfigError::InvalidEnvConfig { prefix, .. }
+            | ConfigError::MissingEnvVars { prefix, .. } => Some(prefix.clone()),
+            ConfigError::InvalidEnvValue { name, .. } | ConfigError::SourceFailed { name, .. } => {
+                Some(name.clone())
             }
-            ConfigError::InvalidLoadingParam => {
-                write!(f, "No configuration source provided. Please configure at least one of:\n\
-                          - Configuration file (set the 'file' parameter)\n\
-                          - Environment variables (set the 'env_prefix' parameter with a valid prefix)")
+            ConfigError::Parse(diagnostic) | ConfigError::Deserialize(diagnostic) => {
+                diagnostic.origin.clone()
             }
-            ConfigError::InvalidEnvConfig { prefix, separator } => {
-                write!(f, "Invalid environment configuration: env prefix '{}' contains separator '{}'.\n\
-                          This will cause configuration loading to fail. Please choose a prefix that doesn't contain the separator,\n\
-                          or use a different separator character.", prefix, separator)
+            _ => None,
+        }
+    }
+
+    /// Gets the locale of `Display`, `message()`, `hint()` and `details()`
+    pub fn locale(&self) -> Locale {
+        match self {
+            ConfigError::Parse(diagnostic) | ConfigError::Deserialize(diagnostic) => {
+                diagnostic.locale
             }
+            ConfigError::Config { locale, .. }
+            | ConfigError::FileNotFound { locale, .. }
+            | ConfigError::ShowSettingsParseError { locale, .. }
+            | ConfigError::InvalidLoadingParam { locale }
+            | ConfigError::InvalidEnvConfig { locale, .. }
+            | ConfigError::InvalidMergeStrategy { locale, .. }
+            | ConfigError::InvalidEnvValue { locale, .. }
+            | ConfigError::MissingEnvVars { locale, .. }
+            | ConfigError::UnknownFileFormat { locale, .. }
+            | ConfigError::FormatNotEnabled { locale, .. }
+            | ConfigError::ValidationFailed { locale, .. }
+            | ConfigError::SnapshotNotFound { locale, .. }
+            | ConfigError::SourceFailed { locale, .. }
+            | ConfigError::LoadCancelled { locale } => *locale,
         }
     }
+
+    /// Renders the error in the given locale from now on
+    pub(crate) fn with_locale(mut self, locale: Locale) -> Self {
+        match &mut self {
+            ConfigError::Parse(diagnostic) | ConfigError::Deserialize(diagnostic) => {
+                diagnostic.locale = locale
+            }
+            ConfigError::Config {
+                locale: current, ..
+            }
+            | ConfigError::FileNotFound {
+                locale: current, ..
+            }
+            | ConfigError::ShowSettingsParseError {
+                locale: current, ..
+            }
+            | ConfigError::InvalidLoadingParam { locale: current }
+            | ConfigError::InvalidEnvConfig {
+                locale: current, ..
+            }
+            | ConfigError::InvalidMergeStrategy {
+                locale: current, ..
+            }
+            | ConfigError::InvalidEnvValue {
+                locale: current, ..
+            }
+            | ConfigError::MissingEnvVars {
+                locale: current, ..
+            }
+            | ConfigError::UnknownFileFormat {
+                locale: current, ..
+            }
+            | ConfigError::FormatNotEnabled {
+                locale: current, ..
+            }
+            | ConfigError::ValidationFailed {
+                locale: current, ..
+            }
+            | ConfigError::SnapshotNotFound {
+                locale: current, ..
+            }
+            | ConfigError::SourceFailed {
+                locale: current, ..
+            }
+            | ConfigError::LoadCancelled { locale: current } => *current = locale,
+        }
+        self
+    }
+
+    /// Gets what went wrong, without the hint, in the locale of the error
+    pub fn message(&self) -> String {
+        self.message_in(self.locale())
+    }
+
+    /// Gets what went wrong, without the hint, in the given locale
+    pub fn message_in(&self, locale: Locale) -> String {
+        i18n::message(self, locale)
+    }
+
+    /// Gets how to fix the error, in the locale of the error
+    pub fn hint(&self) -> String {
+        self.hint_in(self.locale())
+    }
+
+    /// Gets how to fix the error, in the given locale
+    pub fn hint_in(&self, locale: Locale) -> String {
+        i18n::hint(self, locale)
+    }
+
+    /// Gets the code, message, key path, source and hint as structured data
+    pub fn details(&self) -> ErrorDetails {
+        self.details_in(self.locale())
+    }
+
+    /// Gets the code, message, key path, source and hint as structured data, in the given locale
+    pub fn details_in(&self, locale: Locale) -> ErrorDetails {
+        ErrorDetails {
+            code: self.code(),
+            message: self.message_in(locale),
+            key_path: self.key_path(),
+            source: self.source_name(),
+            hint: self.hint_in(locale),
+        }
+    }
+
+    /// Renders the message, snippet and hint like `Display`, in the given locale
+    pub fn to_string_in(&self, locale: Locale) -> String {
+        let mut text = self.message_in(locale);
+        if let Some(snippet) = self.diagnostic().and_then(|d| d.snippet.as_ref()) {
+            text.push_str(&format!("\n{}", snippet));
+        }
+        text.push_str(&format!("\n{}", self.hint_in(locale)));
+        text
+    }
+}
+
+/// Gets the key of a config crate error, if it names one
+fn config_error_key(err: &config::ConfigError) -> Option<String> {
+    match err {
+        config::ConfigError::NotFound(key) => Some(key.clone()),
+        config::ConfigError::Type { key, .. } => key.clone(),
+        config::ConfigError::At { error, key, .. } => {
+            key.clone().or_else(|| config_error_key(error))
+        }
+        _ => None,
+    }
+}
+
+/// Gets the origin of a config crate error, if it names one
+fn config_error_origin(err: &config::ConfigError) -> Option<String> {
+    match err {
+        config::ConfigError::FileParse { uri, .. } => uri.clone(),
+        config::ConfigError::Type { origin, .. } => origin.clone(),
+        config::ConfigError::At { error, origin, .. } => {
+            origin.clone().or_else(|| config_error_origin(error))
+        }
+        _ => None,
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_string_in(self.locale()))
+    }
 }
 
 impl std::error::Error for ConfigError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            ConfigError::Config(err) => Some(err),
+            ConfigError::Config { error, .. } => Some(error),
+            ConfigError::Parse(diagnostic) | ConfigError::Deserialize(diagnostic) => {
+                Some(diagnostic.cause())
+            }
             _ => None,
         }
     }
 }
 
 impl From<config::ConfigError> for ConfigError {
-    fn from(err: config::ConfigError) -> Self {
-        ConfigError::Config(err)
+    fn from(error: config::ConfigError) -> Self {
+        ConfigError::Config {
+            error,
+            locale: Locale::from_env(),
+        }
     }
 }
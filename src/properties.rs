@@ -0,0 +1,240 @@
+use config::{Map, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Error raised while parsing a `.properties` file
+#[derive(Debug)]
+pub struct PropertiesError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PropertiesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}", self.message, self.line)
+    }
+}
+
+impl Error for PropertiesError {}
+
+/// Parses Java-style `.properties` content into key/value pairs
+///
+/// Supports `#` and `!` comments, `=`, `:` or whitespace separators, line continuations
+/// with a trailing backslash, and the escapes `\t`, `\n`, `\r`, `\f` and `\uXXXX`, where
+/// characters outside the BMP are written as a surrogate pair, e.g. `\uD83D\uDE00`.
+/// Keys are returned as written; dotted keys such as `server.port` and indexed keys such
+/// as `servers[0].host` are expanded into nested values when the layer is built, so a key
+/// cannot also be the parent of another, e.g. `logging.level` and `logging.level.root`.
+/// Values are kept as strings and converted according to the target field type.
+pub(crate) fn parse(content: &str, origin: &String) -> Result<Map<String, Value>, PropertiesError> {
+    let mut root = Map::new();
+    let mut key_lines = HashMap::new();
+
+    for (line_number, line) in logical_lines(content) {
+        let (key, value) = split_entry(&line);
+        let key = unescape(key, line_number)?;
+        let value = unescape(value, line_number)?;
+
+        if key.is_empty() || key.split('.').any(str::is_empty) {
+            return Err(PropertiesError {
+                line: line_number,
+                message: format!("invalid key '{}'", key),
+            });
+        }
+        key_lines.insert(key.clone(), line_number);
+        root.insert(key, Value::new(Some(origin), value));
+    }
+
+    check_parents(&key_lines)?;
+    Ok(root)
+}
+
+/// Checks whether sniffed content reads as `.properties` rather than as any text
+///
+/// The parser takes almost any line for a key, so sniffing asks for more: every entry
+/// sets its value with `=` or `:`, and no line is an INI section header such as `[server]`.
+pub(crate) fn is_likely(content: &str) -> bool {
+    let lines = logical_lines(content);
+    !lines.is_empty()
+        && lines.iter().all(|(_, line)| {
+            let (key, _) = split_entry(line);
+            !line.starts_with('[') && line[key.len()..].trim_start().starts_with(['=', ':'])
+        })
+}
+
+/// Finds the 1-based line and column of the value of a key, as written once unescaped
+///
+/// The last entry of the key wins, as when parsing; an entry continued over several lines
+/// is located on its first line.
+pub(crate) fn locate(content: &str, key: &str) -> Option<(usize, usize)> {
+    let raw_lines: Vec<&str> = content.lines().collect();
+    logical_lines(content)
+        .into_iter()
+        .rev()
+        .find_map(|(line_number, line)| {
+            let (raw_key, value) = split_entry(&line);
+            if unescape(raw_key, line_number).ok()? != key {
+                return None;
+            }
+            // The logical line starts at the first non-blank character of its first line
+            let raw = raw_lines.get(line_number - 1)?;
+            let indent = raw.len() - raw.trim_start().len();
+            let end = (indent + line.len() - value.len()).min(raw.len());
+            Some((line_number, raw.get(..end)?.chars().count() + 1))
+        })
+}
+
+/// Checks that no key is set both as a value and as the parent of another key
+///
+/// The error is reported at the later of the two lines.
+fn check_parents(key_lines: &HashMap<String, usize>) -> Result<(), PropertiesError> {
+    let mut conflicts: Vec<(usize, &str, usize, &str)> = Vec::new();
+    for (key, line) in key_lines {
+        for (index, c) in key.char_indices() {
+            if c != '.' && c != '[' {
+                continue;
+            }
+            let parent = &key[..index];
+            if let Some(parent_line) = key_lines.get(parent) {
+                conflicts.push(((*line).max(*parent_line), parent, *parent_line, key));
+            }
+        }
+    }
+
+    match conflicts.into_iter().min() {
+        Some((line, parent, parent_line, key)) => Err(PropertiesError {
+            line,
+            message: format!(
+                "key '{}' at line {} is set as a value and as the parent of '{}' at line {}",
+                parent, parent_line, key, key_lines[key]
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Joins continued lines and drops blank and comment lines, keeping the starting line number
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+
+    for (index, raw) in content.lines().enumerate() {
+        let trimmed = raw.trim_start();
+        let (line_number, mut line) = match current.take() {
+            Some((start, mut line)) => {
+                line.push_str(trimmed);
+                (start, line)
+            }
+            None => {
+                if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+                    continue;
+                }
+                (index + 1, trimmed.to_string())
+            }
+        };
+
+        if ends_with_continuation(&line) {
+            line.pop();
+            current = Some((line_number, line));
+        } else {
+            lines.push((line_number, line));
+        }
+    }
+
+    if let Some(last) = current {
+        lines.push(last);
+    }
+    lines
+}
+
+/// A line continues when it ends with an odd number of backslashes
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// Splits a logical line at the first unescaped `=`, `:` or whitespace
+fn split_entry(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '=' | ':' => return (&line[..index], line[index + 1..].trim_start()),
+            c if c.is_whitespace() => {
+                let rest = line[index..].trim_start();
+                let rest = rest
+                    .strip_prefix('=')
+                    .or_else(|| rest.strip_prefix(':'))
+                    .unwrap_or(rest);
+                return (&line[..index], rest.trim_start());
+            }
+            _ => {}
+        }
+    }
+    (line, "")
+}
+
+fn unescape(raw: &str, line_number: usize) -> Result<String, PropertiesError> {
+    let mut result = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('f') => result.push('\u{c}'),
+            Some('u') => {
+                let unit = read_code_unit(&mut chars, line_number)?;
+                let code_point = match unit {
+                    0xD800..=0xDBFF => {
+                        // A high surrogate must be followed by the escaped low surrogate
+                        let low = match (chars.next(), chars.next()) {
+                            (Some('\\'), Some('u')) => read_code_unit(&mut chars, line_number)?,
+                            _ => return Err(unpaired_surrogate(unit, line_number)),
+                        };
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(unpaired_surrogate(unit, line_number));
+                        }
+                        0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                    }
+                    0xDC00..=0xDFFF => return Err(unpaired_surrogate(unit, line_number)),
+                    _ => unit,
+                };
+                // Every code point outside the surrogate range is a char
+                result.extend(char::from_u32(code_point));
+            }
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+
+    Ok(result)
+}
+
+/// Reads the four hex digits of a `\uXXXX` escape
+fn read_code_unit(chars: &mut std::str::Chars, line_number: usize) -> Result<u32, PropertiesError> {
+    let hex: String = chars.by_ref().take(4).collect();
+    u32::from_str_radix(&hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 4)
+        .ok_or_else(|| PropertiesError {
+            line: line_number,
+            message: format!("invalid unicode escape '\\u{}'", hex),
+        })
+}
+
+fn unpaired_surrogate(unit: u32, line_number: usize) -> PropertiesError {
+    PropertiesError {
+        line: line_number,
+        message: format!("unpaired surrogate in unicode escape '\\u{:04X}'", unit),
+    }
+}
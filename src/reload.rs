@@ -0,0 +1,311 @@
+use crate::i18n::{self, Locale, Warning};
+use crate::loading::load_config_with_report;
+use crate::models::{ConfigError, LoadingParam, OwnedLoadingParam};
+use crate::report::LoadReport;
+use crate::subscription::{ConfigChange, Subscribers, SubscriptionId};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::SystemTime;
+
+/// Number of snapshots, and of failures, a `ConfigHandle` keeps by default
+pub const DEFAULT_HISTORY_SIZE: usize = 5;
+
+type Validator<T> = Box<dyn Fn(&T) -> Result<(), String> + Send + Sync>;
+
+/// A configuration published by a `ConfigHandle`
+#[derive(Debug)]
+pub struct ConfigSnapshot<T> {
+    pub version: u64,          // Starts at 1 and increases with each successful load
+    pub config: T,             // Loaded and validated configuration
+    pub report: LoadReport,    // What the load that produced it did
+    pub loaded_at: SystemTime, // When it was loaded
+}
+
+/// A reload that failed, leaving the previous snapshot published
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReloadFailure {
+    pub code: &'static str, // Code of the error, e.g. `dumbo_config::validation_failed`
+    pub reason: String,     // Message of the error, in the locale of the loading parameters
+    pub kept_version: u64,  // Version that stayed published
+    pub failed_at: SystemTime, // When the reload failed
+}
+
+/// Builds a `ConfigHandle`; see `ConfigHandle::builder`
+pub struct ConfigHandleBuilder<T> {
+    param: OwnedLoadingParam,
+    validator: Option<Validator<T>>,
+    history_size: usize,
+}
+
+/// Holds the current configuration of a long-running service and reloads it on demand
+///
+/// A reload loads the configuration again with the same parameters and runs the validator;
+/// the new configuration is only published when both succeed. Otherwise the previous
+/// snapshot stays published and the failure is recorded. The last snapshots are kept
+/// so that an earlier one can be published again with `rollback`.
+///
+/// Subscribers are told about the values that changed under the key paths they watch
+/// whenever another snapshot is published.
+pub struct ConfigHandle<T> {
+    pub(crate) param: OwnedLoadingParam, // Parameters every reload loads with
+    validator: Option<Validator<T>>,
+    history_size: usize,
+    pub(crate) locale: Locale, // Locale of warnings and recorded failures
+    reloading: Mutex<()>,      // Held by a reload from loading to publishing
+    state: RwLock<State<T>>,
+    subscribers: Subscribers,
+}
+
+struct State<T> {
+    history: VecDeque<Arc<ConfigSnapshot<T>>>, // Published snapshots, the current one last
+    failures: VecDeque<ReloadFailure>,         // Failed reloads, the latest last
+    next_version: u64,
+}
+
+impl<T> ConfigHandleBuilder<T>
+where
+    T: for<'de> Deserialize<'de> + Serialize,
+{
+    /// Sets the validation a loaded configuration must pass before it is published
+    ///
+    /// The error of the validator, e.g. a `String`, becomes `ConfigError::ValidationFailed`.
+    pub fn with_validator<F, E>(mut self, validator: F) -> Self
+    where
+        F: Fn(&T) -> Result<(), E> + Send + Sync + 'static,
+        E: Display,
+    {
+        self.validator = Some(Box::new(move |config| {
+            validator(config).map_err(|err| err.to_string())
+        }));
+        self
+    }
+
+    /// Sets how many snapshots, and failures, are kept; at least the current snapshot is
+    pub fn with_history_size(mut self, history_size: usize) -> Self {
+        self.history_size = history_size.max(1);
+        self
+    }
+
+    /// Loads and validates the initial configuration
+    ///
+    /// Unlike a reload, there is no previous snapshot to keep, so a failure is returned as is.
+    pub fn build(self) -> Result<ConfigHandle<T>, ConfigError> {
+        let mut handle = ConfigHandle {
+            locale: i18n::resolve(self.param.locale),
+            param: self.param,
+            validator: self.validator,
+            history_size: self.history_size,
+            reloading: Mutex::new(()),
+            state: RwLock::new(State {
+                history: VecDeque::new(),
+                failures: VecDeque::new(),
+                next_version: 1,
+            }),
+            subscribers: Subscribers::default(),
+        };
+        let (config, report) = handle.load()?;
+        let state = handle
+            .state
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        let snapshot = state.snapshot(config, report);
+        state.publish(snapshot, handle.history_size);
+        Ok(handle)
+    }
+}
+
+impl<T> ConfigHandle<T>
+where
+    T: for<'de> Deserialize<'de> + Serialize,
+{
+    /// Starts building a handle that loads configuration with the given parameters
+    ///
+    /// The handle keeps its own copy of the parameters, so it may outlive them.
+    pub fn builder(param: LoadingParam) -> ConfigHandleBuilder<T> {
+        ConfigHandleBuilder {
+            param: OwnedLoadingParam::new(&param),
+            validator: None,
+            history_size: DEFAULT_HISTORY_SIZE,
+        }
+    }
+
+    /// Gets the published snapshot
+    pub fn current(&self) -> Arc<ConfigSnapshot<T>> {
+        self.read().current()
+    }
+
+    /// Loads and validates the configuration again, and publishes it if both succeed
+    ///
+    /// On failure the previous snapshot stays published, the failure is logged and recorded,
+    /// and the error is returned. Reloads from several threads, e.g. on SIGHUP and on a file
+    /// change, run one at a time.
+    pub fn reload(&self) -> Result<Arc<ConfigSnapshot<T>>, ConfigError> {
+        // Held until published, so a slower load cannot publish older content over a newer one
+        let reloading = self
+            .reloading
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let loaded = self.load();
+        let mut state = self.write();
+        match loaded {
+            Ok((config, report)) => {
+                let previous = state.current();
+                let snapshot = state.snapshot(config, report);
+                state.publish(snapshot.clone(), self.history_size);
+                drop(state);
+                drop(reloading);
+                log::info!("Published configuration version {}", snapshot.version);
+                self.notify(&previous, &snapshot);
+                Ok(snapshot)
+            }
+            Err(err) => {
+                let kept_version = state.current().version;
+                Warning::ReloadFailed {
+                    version: kept_version,
+                    error: &err,
+                }
+                .log(self.locale);
+                state.failures.push_back(ReloadFailure {
+                    code: err.code(),
+                    reason: err.message_in(self.locale),
+                    kept_version,
+                    failed_at: SystemTime::now(),
+                });
+                if state.failures.len() > self.history_size {
+                    state.failures.pop_front();
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Publishes again the snapshot with the given version from the history
+    pub fn rollback(&self, version: u64) -> Result<Arc<ConfigSnapshot<T>>, ConfigError> {
+        let mut state = self.write();
+        let snapshot = state
+            .history
+            .iter()
+            .find(|snapshot| snapshot.version == version)
+            .cloned()
+            .ok_or(ConfigError::SnapshotNotFound {
+                version,
+                locale: self.locale,
+            })?;
+        let previous = state.current();
+        state.publish(snapshot.clone(), self.history_size);
+        drop(state);
+        log::info!("Rolled back configuration to version {}", version);
+        self.notify(&previous, &snapshot);
+        Ok(snapshot)
+    }
+
+    /// Gets the kept snapshots, the least recently published first and the current one last
+    pub fn history(&self) -> Vec<Arc<ConfigSnapshot<T>>> {
+        self.read().history.iter().cloned().collect()
+    }
+
+    /// Gets the recorded reload failures, the latest last
+    pub fn failures(&self) -> Vec<ReloadFailure> {
+        self.read().failures.iter().cloned().collect()
+    }
+
+    /// Calls the callback with the changed values under the given key path patterns
+    ///
+    /// Patterns are dotted key paths such as `db.pool_size`; `*` matches any one segment,
+    /// and a pattern also covers the keys below it, so `http.*` watches all of `http`.
+    /// Callbacks run on the thread that reloads or rolls back, after the snapshot is published.
+    pub fn subscribe<F>(&self, patterns: &[&str], callback: F) -> SubscriptionId
+    where
+        F: Fn(&ConfigChange) + Send + Sync + 'static,
+    {
+        self.subscribers.add_callback(patterns, callback)
+    }
+
+    /// Sends the changed values under the given key path patterns to the returned channel
+    ///
+    /// The subscription ends when the receiver is dropped.
+    pub fn subscribe_channel(&self, patterns: &[&str]) -> Receiver<ConfigChange> {
+        self.subscribers.add_channel(patterns)
+    }
+
+    /// Sends the changed values under the given key path patterns to the returned async stream
+    ///
+    /// The subscription ends when the stream is dropped.
+    #[cfg(feature = "tokio")]
+    pub fn subscribe_stream(&self, patterns: &[&str]) -> crate::subscription::ChangeStream {
+        self.subscribers.add_stream(patterns)
+    }
+
+    /// Cancels a subscription; returns whether it existed
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.subscribers.remove(id)
+    }
+
+    fn notify(&self, old: &ConfigSnapshot<T>, new: &ConfigSnapshot<T>) {
+        if self.subscribers.is_empty() || old.version == new.version {
+            return;
+        }
+        match (
+            serde_json::to_value(&old.config),
+            serde_json::to_value(&new.config),
+        ) {
+            (Ok(old_value), Ok(new_value)) => self
+                .subscribers
+                .notify((old.version, &old_value), (new.version, &new_value)),
+            (Err(err), _) | (_, Err(err)) => Warning::NotifyFailed { error: &err }.log(self.locale),
+        }
+    }
+
+    fn load(&self) -> Result<(T, LoadReport), ConfigError> {
+        let (config, report) = load_config_with_report(&self.param.param())?;
+        if let Some(validator) = &self.validator {
+            validator(&config).map_err(|reason| ConfigError::ValidationFailed {
+                reason,
+                locale: self.locale,
+            })?;
+        }
+        Ok((config, report))
+    }
+
+    // Every update of the state completes before the lock is released,
+    // so the state of a poisoned lock is still consistent
+    fn read(&self) -> RwLockReadGuard<'_, State<T>> {
+        self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, State<T>> {
+        self.state.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> State<T> {
+    fn current(&self) -> Arc<ConfigSnapshot<T>> {
+        self.history
+            .back()
+            .cloned()
+            .expect("a handle always has a published snapshot")
+    }
+
+    fn snapshot(&mut self, config: T, report: LoadReport) -> Arc<ConfigSnapshot<T>> {
+        let version = self.next_version;
+        self.next_version += 1;
+        Arc::new(ConfigSnapshot {
+            version,
+            config,
+            report,
+            loaded_at: SystemTime::now(),
+        })
+    }
+
+    /// Makes the snapshot the current one, moving it to the end if it is already kept
+    fn publish(&mut self, snapshot: Arc<ConfigSnapshot<T>>, history_size: usize) {
+        self.history.retain(|kept| kept.version != snapshot.version);
+        self.history.push_back(snapshot);
+        while self.history.len() > history_size {
+            self.history.pop_front();
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::fields::KnownFields;
+use crate::format::Format;
+use crate::i18n::{Locale, Warning};
+use crate::merge::MERGE_DIRECTIVE_KEY;
+use crate::telemetry;
+use config::{Map, Value, ValueKind};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// What a call to `load_config_with_report` did, e.g. for a startup health endpoint
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LoadReport {
+    pub sources: Vec<SourceReport>, // Sources in order of increasing priority
+    pub warnings: Vec<LoadWarning>, // Warnings logged while loading
+    pub ignored_keys: Vec<String>,  // Dotted keys the target type does not declare
+    pub duration: Duration,         // Time taken to load the configuration
+    #[serde(skip)]
+    locale: Locale,
+}
+
+/// Kind of configuration source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    File,
+    Env,
+    Async, // Source loaded by `load_config_with_sources_async`
+}
+
+impl SourceKind {
+    /// Gets the name of the kind, as serialized, e.g. `env`
+    pub fn name(&self) -> &'static str {
+        match self {
+            SourceKind::File => "file",
+            SourceKind::Env => "env",
+            SourceKind::Async => "async",
+        }
+    }
+}
+
+/// A configuration source and what it provided
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceReport {
+    pub name: String,     // File path, `env:` followed by the prefix, or source name
+    pub kind: SourceKind, // File, environment variables or async source
+    pub path: Option<PathBuf>, // Configuration file, for file sources
+    pub format: Option<Format>, // Format the file was parsed as, for file sources
+    pub keys: usize,      // Number of values the source provided
+}
+
+/// A warning logged while loading, as data
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoadWarning {
+    pub code: &'static str, // Stable warning code, e.g. `dumbo_config::no_env_vars`
+    pub message: String,    // Warning text, in the locale of the loading parameters
+}
+
+impl LoadReport {
+    /// Creates an empty report whose warnings are written in the given locale
+    pub(crate) fn new(locale: Locale) -> Self {
+        Self {
+            locale,
+            ..Default::default()
+        }
+    }
+
+    /// Logs the warning and records it
+    pub(crate) fn warn(&mut self, warning: Warning) {
+        warning.log(self.locale);
+        self.warnings.push(LoadWarning {
+            code: warning.code(),
+            message: warning.text(self.locale),
+        });
+    }
+
+    /// Records a loaded, or skipped, source
+    pub(crate) fn add_source(&mut self, source: SourceReport) {
+        telemetry::source_loaded(&source);
+        self.sources.push(source);
+    }
+
+    /// Records the keys of the merged values that the target type does not declare
+    ///
+    /// Tables the target type reads as maps, or that could not be probed, are not reported,
+    /// nor are the values of `control_vars`, e.g. `MY_APP__SHOW_SETTINGS`, which the crate
+    /// reads itself.
+    pub(crate) fn record_ignored_keys(
+        &mut self,
+        values: &Map<String, Value>,
+        known_fields: &KnownFields,
+        control_vars: &[String],
+    ) {
+        let mut ignored = Vec::new();
+        collect_ignored(values, "", known_fields, &mut ignored);
+        self.ignored_keys.extend(
+            ignored
+                .into_iter()
+                .filter(|(_, value)| !from_vars(value, control_vars))
+                .map(|(key_path, _)| key_path),
+        );
+        self.ignored_keys.sort();
+        self.ignored_keys.dedup();
+    }
+}
+
+/// Counts the values of a layer, leaving out the `_merge` directive table
+pub(crate) fn count_values(values: &Map<String, Value>) -> usize {
+    values
+        .iter()
+        .filter(|(key, _)| key.as_str() != MERGE_DIRECTIVE_KEY)
+        .map(|(_, value)| count_leaves(value))
+        .sum()
+}
+
+fn count_leaves(value: &Value) -> usize {
+    match &value.kind {
+        ValueKind::Table(table) => table.values().map(count_leaves).sum(),
+        ValueKind::Array(items) => items.iter().map(count_leaves).sum(),
+        _ => 1,
+    }
+}
+
+/// Checks whether every value of the tree comes from one of the environment variables
+fn from_vars(value: &Value, vars: &[String]) -> bool {
+    match &value.kind {
+        ValueKind::Table(table) if !table.is_empty() => {
+            table.values().all(|value| from_vars(value, vars))
+        }
+        _ => value
+            .origin()
+            .is_some_and(|origin| vars.iter().any(|var| var == origin)),
+    }
+}
+
+fn collect_ignored<'a>(
+    values: &'a Map<String, Value>,
+    path: &str,
+    known_fields: &KnownFields,
+    ignored: &mut Vec<(String, &'a Value)>,
+) {
+    let fields = known_fields.fields_at(path);
+    if fields.is_empty() {
+        return;
+    }
+
+    for (key, value) in values {
+        let key_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        if !fields.contains(&key.as_str()) {
+            ignored.push((key_path, value));
+            continue;
+        }
+        match &value.kind {
+            ValueKind::Table(table) => collect_ignored(table, &key_path, known_fields, ignored),
+            // Elements of sequences share the path of the sequence itself
+            ValueKind::Array(items) => {
+                for item in items {
+                    if let ValueKind::Table(table) = &item.kind {
+                        collect_ignored(table, &key_path, known_fields, ignored);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
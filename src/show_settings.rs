@@ -0,0 +1,346 @@
+use crate::i18n::Locale;
+use crate::merge::Layer;
+use crate::models::{ConfigError, EnvConfig, KeyMapping};
+use config::{Map, Value, ValueKind};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Format the loaded configuration is shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SettingsFormat {
+    Json,
+    Yaml,
+    Toml,
+    /// `PREFIX__KEY=value` lines, as read back by the env prefix with its key mapping
+    Env,
+    /// Aligned dotted keys and values
+    Table,
+    /// Only the values the env layers override, with the value of each layer
+    Diff,
+}
+
+/// Where the loaded configuration is shown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SettingsDestination {
+    Log,
+    Stderr,
+    File(PathBuf),
+}
+
+/// What a SHOW_SETTINGS environment variable asks for
+#[derive(Debug, Clone)]
+pub(crate) struct ShowSettings {
+    pub format: SettingsFormat,
+    pub destination: SettingsDestination,
+    prefix: String,
+    separator: String,
+    key_mapping: KeyMapping, // Inverted to name the variables of the env format
+}
+
+impl ShowSettings {
+    /// Parses the value of the SHOW_SETTINGS variable of the given prefix
+    ///
+    /// The value is a boolean, or a format (`json`, `yaml`, `toml`, `env`, `table`) optionally
+    /// followed by `:` and a destination (`log`, `stderr` or a file path), e.g. `toml:stderr`
+    /// or `env:/tmp/app.env`. True values show JSON in the log; false values give `None`.
+    ///
+    /// `diff` only shows the keys an env layer sets whose effective value differs from the
+    /// file, listing the value of each layer. Keys the file does not set are always shown, as
+    /// the defaults of the target type are not known.
+    pub(crate) fn parse(value: &str, env_config: &EnvConfig) -> Result<Option<Self>, ConfigError> {
+        let (head, destination) = match value.split_once(':') {
+            Some((head, destination)) => (head, Some(destination.trim())),
+            None => (value, None),
+        };
+        let format = match head.trim().to_lowercase().as_str() {
+            "false" | "0" | "no" | "off" if destination.is_none() => return Ok(None),
+            "true" | "1" | "yes" | "on" | "json" => SettingsFormat::Json,
+            "yaml" | "yml" => SettingsFormat::Yaml,
+            "toml" => SettingsFormat::Toml,
+            "env" => SettingsFormat::Env,
+            "table" => SettingsFormat::Table,
+            "diff" => SettingsFormat::Diff,
+            _ => {
+                return Err(ConfigError::ShowSettingsParseError {
+                    value: value.to_string(),
+                    locale: Locale::from_env(),
+                })
+            }
+        };
+        let destination = match destination {
+            None | Some("") => SettingsDestination::Log,
+            Some(name) if name.eq_ignore_ascii_case("log") => SettingsDestination::Log,
+            Some(name) if name.eq_ignore_ascii_case("stderr") => SettingsDestination::Stderr,
+            Some(path) => SettingsDestination::File(PathBuf::from(path)),
+        };
+
+        Ok(Some(ShowSettings {
+            format,
+            destination,
+            prefix: env_config.name.clone(),
+            separator: env_config.get_separator().to_string(),
+            key_mapping: env_config.key_mapping.clone(),
+        }))
+    }
+
+    /// Renders the configuration in the requested format
+    ///
+    /// The layers are only used by the diff format.
+    pub(crate) fn render<T>(
+        &self,
+        config: &T,
+        layers: &Provenance,
+    ) -> Result<String, Box<dyn Error>>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(config)?;
+        match self.format {
+            SettingsFormat::Json => Ok(serde_json::to_string_pretty(&value)?),
+            SettingsFormat::Yaml => render_yaml(&value),
+            SettingsFormat::Toml => render_toml(&value),
+            SettingsFormat::Env => {
+                let mut lines = Vec::new();
+                flatten(&value, &mut Vec::new(), &mut |segments, text| {
+                    let names: Vec<String> = segments
+                        .iter()
+                        .map(|segment| match segment {
+                            Segment::Key(key) => self.key_mapping.unmap_key(key),
+                            Segment::Index(index) => index.to_string(),
+                        })
+                        .collect();
+                    lines.push(format!(
+                        "{}{}{}={}",
+                        self.prefix,
+                        self.separator,
+                        names.join(&self.separator),
+                        shell_quote(text)
+                    ));
+                });
+                Ok(lines.join("\n"))
+            }
+            SettingsFormat::Table => {
+                let mut rows = Vec::new();
+                flatten(&value, &mut Vec::new(), &mut |segments, text| {
+                    rows.push((key_path(segments), text.to_string()));
+                });
+                let width = rows
+                    .iter()
+                    .map(|(key, _)| key.chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    .max("KEY".len());
+                let mut lines = vec![format!("{:width$}  VALUE", "KEY", width = width)];
+                lines.extend(
+                    rows.iter()
+                        .map(|(key, text)| format!("{:width$}  {}", key, text, width = width)),
+                );
+                Ok(lines.join("\n"))
+            }
+            SettingsFormat::Diff => Ok(render_diff(&value, layers)),
+        }
+    }
+}
+
+/// Layers the diff format compares the effective configuration with
+#[derive(Debug, Default)]
+pub(crate) struct Provenance<'a> {
+    pub base: Option<&'a Layer>, // Configuration file layer, if any
+    pub overrides: &'a [Layer],  // Env layers, in order of increasing priority
+}
+
+/// Lists each key an override layer sets and whose effective value differs from the base
+///
+/// A key the base does not set counts as differing. Each key is followed by the value of
+/// every layer that sets it, the winning layer last.
+fn render_diff(value: &JsonValue, layers: &Provenance) -> String {
+    let base = layers.base.map(|layer| layer_values(&layer.values));
+    let overrides: Vec<(&str, HashMap<String, String>)> = layers
+        .overrides
+        .iter()
+        .map(|layer| (layer.name.as_str(), layer_values(&layer.values)))
+        .collect();
+
+    let mut lines = Vec::new();
+    flatten(value, &mut Vec::new(), &mut |segments, effective| {
+        let key = key_path(segments);
+        let set_by: Vec<(&str, &String)> = overrides
+            .iter()
+            .filter_map(|(name, values)| values.get(&key).map(|text| (*name, text)))
+            .collect();
+        let base_text = base.as_ref().and_then(|values| values.get(&key));
+        if set_by.is_empty() || base_text.map(String::as_str) == Some(effective) {
+            return;
+        }
+
+        lines.push(format!("{} = {}", key, effective));
+        if let Some(layer) = layers.base {
+            let text = base_text.map_or("(not set)", String::as_str);
+            lines.push(format!("    {} = {}", layer.name, text));
+        }
+        let winner = set_by.len() - 1;
+        for (index, (name, text)) in set_by.iter().enumerate() {
+            let mark = if index == winner { "  (wins)" } else { "" };
+            lines.push(format!("    {} = {}{}", name, text, mark));
+        }
+    });
+
+    if lines.is_empty() {
+        "No values are overridden".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+/// Gets the text of each scalar of a layer by its key path
+fn layer_values(values: &Map<String, Value>) -> HashMap<String, String> {
+    let value = JsonValue::Object(
+        values
+            .iter()
+            .map(|(key, value)| (key.clone(), to_json(value)))
+            .collect(),
+    );
+    let mut texts = HashMap::new();
+    flatten(&value, &mut Vec::new(), &mut |segments, text| {
+        texts.insert(key_path(segments), text.to_string());
+    });
+    texts
+}
+
+/// Converts a layer value to JSON, keeping its scalars as text
+fn to_json(value: &Value) -> JsonValue {
+    match &value.kind {
+        ValueKind::Nil => JsonValue::Null,
+        ValueKind::Table(table) => JsonValue::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.clone(), to_json(value)))
+                .collect(),
+        ),
+        ValueKind::Array(items) => JsonValue::Array(items.iter().map(to_json).collect()),
+        scalar => JsonValue::String(scalar.to_string()),
+    }
+}
+
+impl fmt::Display for SettingsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsFormat::Json => write!(f, "json"),
+            SettingsFormat::Yaml => write!(f, "yaml"),
+            SettingsFormat::Toml => write!(f, "toml"),
+            SettingsFormat::Env => write!(f, "env"),
+            SettingsFormat::Table => write!(f, "table"),
+            SettingsFormat::Diff => write!(f, "diff"),
+        }
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn render_yaml(value: &JsonValue) -> Result<String, Box<dyn Error>> {
+    Ok(serde_yaml::to_string(value)?)
+}
+
+#[cfg(not(feature = "yaml"))]
+fn render_yaml(_value: &JsonValue) -> Result<String, Box<dyn Error>> {
+    Err("the 'yaml' feature of dumbo_config is disabled".into())
+}
+
+/// Renders TOML, leaving out null values, which TOML cannot represent
+#[cfg(feature = "toml")]
+fn render_toml(value: &JsonValue) -> Result<String, Box<dyn Error>> {
+    Ok(toml::to_string(&without_nulls(value))?)
+}
+
+#[cfg(not(feature = "toml"))]
+fn render_toml(_value: &JsonValue) -> Result<String, Box<dyn Error>> {
+    Err("the 'toml' feature of dumbo_config is disabled".into())
+}
+
+#[cfg(feature = "toml")]
+fn without_nulls(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.clone(), without_nulls(value)))
+                .collect(),
+        ),
+        JsonValue::Array(items) => JsonValue::Array(
+            items
+                .iter()
+                .filter(|value| !value.is_null())
+                .map(without_nulls)
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Segment of the path to a scalar
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Calls `visit` with the path and the text of each scalar, skipping nulls
+///
+/// Array items are keyed by their index, as read back by indexed arrays.
+fn flatten(
+    value: &JsonValue,
+    segments: &mut Vec<Segment>,
+    visit: &mut dyn FnMut(&[Segment], &str),
+) {
+    match value {
+        JsonValue::Object(map) => {
+            for (key, value) in map {
+                segments.push(Segment::Key(key.clone()));
+                flatten(value, segments, visit);
+                segments.pop();
+            }
+        }
+        JsonValue::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                segments.push(Segment::Index(index));
+                flatten(value, segments, visit);
+                segments.pop();
+            }
+        }
+        JsonValue::Null => {}
+        JsonValue::String(text) => visit(segments, text),
+        other => visit(segments, &other.to_string()),
+    }
+}
+
+/// Writes segments as a key path, e.g. `servers[0].host`
+fn key_path(segments: &[Segment]) -> String {
+    let mut path = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Key(key) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            Segment::Index(index) => path.push_str(&format!("[{}]", index)),
+        }
+    }
+    path
+}
+
+/// Quotes a value for a shell or an env file when it contains special characters
+fn shell_quote(text: &str) -> String {
+    let plain = !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:@,+=%".contains(c));
+    if plain {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', "'\\''"))
+    }
+}
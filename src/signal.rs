@@ -0,0 +1,65 @@
+use crate::reload::ConfigHandle;
+use serde::{Deserialize, Serialize};
+use signal_hook::consts::SIGHUP;
+use signal_hook::iterator::{Handle, Signals};
+use std::io;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// Reloads a `ConfigHandle` each time the process receives SIGHUP
+///
+/// Listening stops when the listener is stopped or dropped.
+pub struct SighupListener {
+    signals: Handle,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T> ConfigHandle<T>
+where
+    T: for<'de> Deserialize<'de> + Serialize + Send + Sync + 'static,
+{
+    /// Reloads the configuration on SIGHUP, with the loading parameters the handle was built with
+    ///
+    /// Reloads run on a listener thread. A failed reload keeps the previous snapshot and is
+    /// logged as a warning by `reload`, with the code, key path and source of the error as
+    /// fields of the record.
+    pub fn reload_on_sighup(self: &Arc<Self>) -> io::Result<SighupListener> {
+        let mut signals = Signals::new([SIGHUP])?;
+        let handle = signals.handle();
+        let config = Arc::clone(self);
+        let thread = thread::Builder::new()
+            .name("dumbo-config-sighup".to_string())
+            .spawn(move || {
+                for _ in signals.forever() {
+                    log::info!("Received SIGHUP, reloading configuration");
+                    // A failed reload is logged and recorded by `reload`
+                    let _ = config.reload();
+                }
+            })?;
+
+        Ok(SighupListener {
+            signals: handle,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl SighupListener {
+    /// Stops listening and waits for a reload in progress to finish
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.signals.close();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for SighupListener {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
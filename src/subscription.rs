@@ -0,0 +1,235 @@
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// A change of the published configuration, limited to the key paths a subscriber watches
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigChange {
+    pub old_version: u64,        // Version that was published before
+    pub new_version: u64,        // Version that is published now
+    pub changes: Vec<KeyChange>, // Changed values, sorted by key path
+}
+
+/// A value that changed between two versions
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyChange {
+    pub key_path: String, // Dotted key path, e.g. `db.pool_size` or `servers[0].host`
+    pub old: Option<JsonValue>, // Value before, `None` when the key was not set
+    pub new: Option<JsonValue>, // Value after, `None` when the key is no longer set
+}
+
+/// Identifies a subscription, to cancel it with `ConfigHandle::unsubscribe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Callback = Arc<dyn Fn(&ConfigChange) + Send + Sync>;
+
+enum Sink {
+    Callback(Callback),
+    Channel(Sender<ConfigChange>),
+    #[cfg(feature = "tokio")]
+    Stream(tokio::sync::mpsc::UnboundedSender<ConfigChange>),
+}
+
+/// Changes of the published configuration as an async stream
+///
+/// Returned by `ConfigHandle::subscribe_stream`; the subscription ends when it is dropped.
+#[cfg(feature = "tokio")]
+pub struct ChangeStream {
+    receiver: tokio::sync::mpsc::UnboundedReceiver<ConfigChange>,
+}
+
+#[cfg(feature = "tokio")]
+impl ChangeStream {
+    /// Waits for the next change; `None` once the handle is dropped
+    pub async fn next(&mut self) -> Option<ConfigChange> {
+        self.receiver.recv().await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl futures_core::Stream for ChangeStream {
+    type Item = ConfigChange;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<ConfigChange>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+struct Subscriber {
+    id: SubscriptionId,
+    patterns: Vec<String>,
+    sink: Sink,
+}
+
+/// Subscribers of a `ConfigHandle`
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    subscribers: Mutex<Vec<Subscriber>>,
+    next_id: AtomicU64,
+}
+
+impl Subscribers {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    pub(crate) fn add_callback<F>(&self, patterns: &[&str], callback: F) -> SubscriptionId
+    where
+        F: Fn(&ConfigChange) + Send + Sync + 'static,
+    {
+        self.add(patterns, Sink::Callback(Arc::new(callback)))
+    }
+
+    pub(crate) fn add_channel(&self, patterns: &[&str]) -> Receiver<ConfigChange> {
+        let (sender, receiver) = mpsc::channel();
+        self.add(patterns, Sink::Channel(sender));
+        receiver
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn add_stream(&self, patterns: &[&str]) -> ChangeStream {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        self.add(patterns, Sink::Stream(sender));
+        ChangeStream { receiver }
+    }
+
+    pub(crate) fn remove(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.lock();
+        let count = subscribers.len();
+        subscribers.retain(|subscriber| subscriber.id != id);
+        subscribers.len() != count
+    }
+
+    /// Sends each subscriber the changed values matching its patterns, if any
+    ///
+    /// Callbacks run after the subscriber list is released, so they may use the handle.
+    /// Channels and streams whose receiver was dropped are unsubscribed.
+    pub(crate) fn notify(&self, old: (u64, &JsonValue), new: (u64, &JsonValue)) {
+        let changes = diff(old.1, new.1);
+        if changes.is_empty() {
+            return;
+        }
+
+        let mut callbacks = Vec::new();
+        self.lock().retain(|subscriber| {
+            let matching: Vec<KeyChange> = changes
+                .iter()
+                .filter(|change| {
+                    subscriber
+                        .patterns
+                        .iter()
+                        .any(|pattern| matches(pattern, &change.key_path))
+                })
+                .cloned()
+                .collect();
+            if matching.is_empty() {
+                return true;
+            }
+            let change = ConfigChange {
+                old_version: old.0,
+                new_version: new.0,
+                changes: matching,
+            };
+            match &subscriber.sink {
+                Sink::Callback(callback) => {
+                    callbacks.push((callback.clone(), change));
+                    true
+                }
+                Sink::Channel(sender) => sender.send(change).is_ok(),
+                #[cfg(feature = "tokio")]
+                Sink::Stream(sender) => sender.send(change).is_ok(),
+            }
+        });
+        for (callback, change) in callbacks {
+            callback(&change);
+        }
+    }
+
+    fn add(&self, patterns: &[&str], sink: Sink) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        self.lock().push(Subscriber {
+            id,
+            patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+            sink,
+        });
+        id
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Subscriber>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Checks whether a key path is watched by a pattern
+///
+/// Segments are separated by `.` or written as `[index]`; `*` matches any one segment,
+/// and a pattern also matches the keys below it, e.g. `http.*` and `http` both match
+/// `http.tls.cert`, and `servers.*.host` matches `servers[0].host`.
+pub(crate) fn matches(pattern: &str, key_path: &str) -> bool {
+    let pattern: Vec<&str> = segments(pattern).collect();
+    let path: Vec<&str> = segments(key_path).collect();
+    pattern.len() <= path.len()
+        && pattern
+            .iter()
+            .zip(&path)
+            .all(|(expected, segment)| *expected == "*" || expected == segment)
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split(['.', '['])
+        .map(|segment| segment.trim_end_matches(']'))
+}
+
+/// Lists the values that differ between two configurations, sorted by key path
+fn diff(old: &JsonValue, new: &JsonValue) -> Vec<KeyChange> {
+    let mut old_values = BTreeMap::new();
+    let mut new_values = BTreeMap::new();
+    collect_leaves(old, String::new(), &mut old_values);
+    collect_leaves(new, String::new(), &mut new_values);
+
+    let mut key_paths: Vec<&String> = old_values.keys().chain(new_values.keys()).collect();
+    key_paths.sort();
+    key_paths.dedup();
+    key_paths
+        .into_iter()
+        .filter(|key_path| old_values.get(*key_path) != new_values.get(*key_path))
+        .map(|key_path| KeyChange {
+            key_path: key_path.clone(),
+            old: old_values.get(key_path).cloned(),
+            new: new_values.get(key_path).cloned(),
+        })
+        .collect()
+}
+
+/// Collects the scalars, nulls and empty tables or arrays by key path
+fn collect_leaves(value: &JsonValue, path: String, leaves: &mut BTreeMap<String, JsonValue>) {
+    match value {
+        JsonValue::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let key_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                collect_leaves(value, key_path, leaves);
+            }
+        }
+        JsonValue::Array(items) if !items.is_empty() => {
+            for (index, value) in items.iter().enumerate() {
+                collect_leaves(value, format!("{}[{}]", path, index), leaves);
+            }
+        }
+        _ => {
+            leaves.insert(path, value.clone());
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::models::{ConfigError, LoadingParam};
+#[cfg(feature = "tracing")]
+use crate::report::SourceKind;
+use crate::report::{LoadReport, SourceReport};
+#[cfg(feature = "tokio")]
+use std::future::Future;
+
+/// Span entered for as long as it is alive
+///
+/// Spans and events are only emitted with the `tracing` feature; otherwise the functions
+/// of this module do nothing, so the loader needs no `cfg` attributes of its own.
+pub(crate) struct Span {
+    #[cfg(feature = "tracing")]
+    _entered: tracing::span::EnteredSpan,
+}
+
+/// Span and subscriber of the caller, for the work of a load handed over to other tasks or threads
+#[cfg(feature = "tokio")]
+pub(crate) struct CallerSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "tracing")]
+    dispatch: tracing::Dispatch,
+}
+
+#[cfg(feature = "tokio")]
+impl CallerSpan {
+    /// Captures the span the caller is in
+    pub(crate) fn current() -> Self {
+        CallerSpan {
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::current(),
+            #[cfg(feature = "tracing")]
+            dispatch: tracing::dispatcher::get_default(tracing::Dispatch::clone),
+        }
+    }
+
+    /// Runs the work in the span of the caller, e.g. on a blocking thread
+    #[cfg(feature = "tracing")]
+    pub(crate) fn in_scope<R>(&self, work: impl FnOnce() -> R) -> R {
+        tracing::dispatcher::with_default(&self.dispatch, || self.span.in_scope(work))
+    }
+
+    /// Runs the work in the span of the caller, e.g. on a blocking thread
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn in_scope<R>(&self, work: impl FnOnce() -> R) -> R {
+        work()
+    }
+
+    /// Polls the future in the span of the caller
+    #[cfg(feature = "tracing")]
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        use tracing::instrument::WithSubscriber;
+        use tracing::Instrument;
+
+        future
+            .instrument(self.span.clone())
+            .with_subscriber(self.dispatch.clone())
+    }
+
+    /// Polls the future in the span of the caller
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        future
+    }
+}
+
+/// Enters the span of a whole configuration load
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn load_span(param: &LoadingParam) -> Span {
+    Span {
+        #[cfg(feature = "tracing")]
+        _entered: {
+            let prefixes: Vec<&str> = param.env_prefix.iter().map(|c| c.name.as_str()).collect();
+            tracing::info_span!(
+                "dumbo_config.load",
+                file = param.file.map(|file| file.display().to_string()),
+                file_format = param.file_format.map(|format| format.to_string()),
+                env_prefixes = prefixes.join(","),
+                profile = param.profile,
+            )
+            .entered()
+        },
+    }
+}
+
+/// Enters the span of the configuration file source
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn file_span(path: &std::path::Path) -> Span {
+    Span {
+        #[cfg(feature = "tracing")]
+        _entered: tracing::info_span!(
+            "dumbo_config.source",
+            source.kind = SourceKind::File.name(),
+            source.path = %path.display(),
+        )
+        .entered(),
+    }
+}
+
+/// Enters the span of an environment variable source
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn env_span(prefix: &str) -> Span {
+    Span {
+        #[cfg(feature = "tracing")]
+        _entered: tracing::info_span!(
+            "dumbo_config.source",
+            source.kind = SourceKind::Env.name(),
+            source.prefix = prefix,
+        )
+        .entered(),
+    }
+}
+
+/// Emits the event of a loaded, or skipped, source
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn source_loaded(source: &SourceReport) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        source.name = %source.name,
+        source.kind = source.kind.name(),
+        source.path = source.path.as_ref().map(|path| path.display().to_string()),
+        source.format = source.format.map(|format| format.to_string()),
+        source.keys = source.keys,
+        "configuration source loaded"
+    );
+}
+
+/// Emits a warning as a `log` record, and as a `tracing` event too with the `tracing` feature
+///
+/// Cargo unifies features, so another crate enabling `tracing` must not take the warnings
+/// away from applications that only read `log` records.
+pub(crate) fn warning(code: &'static str, message: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(warning.code = code, "{}", message);
+    log::warn!("warning.code" = code; "{}", message);
+}
+
+/// Emits a warning caused by an error, like `warning`, with the code, key path and source
+/// of the error as fields of the record and event
+pub(crate) fn error_warning(code: &'static str, message: &str, error: &ConfigError) {
+    let key_path = error.key_path();
+    let source = error.source_name();
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        warning.code = code,
+        error.code = error.code(),
+        error.key_path = key_path,
+        error.source = source,
+        "{}",
+        message
+    );
+    log::warn!(
+        "warning.code" = code,
+        "error.code" = error.code(),
+        "error.key_path" = key_path.as_deref(),
+        "error.source" = source.as_deref();
+        "{}",
+        message
+    );
+}
+
+/// Emits the event of a successful load
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn load_finished(report: &LoadReport) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        sources = report.sources.len(),
+        warnings = report.warnings.len(),
+        ignored_keys = report.ignored_keys.len(),
+        duration_ms = report.duration.as_secs_f64() * 1000.0,
+        "configuration loaded"
+    );
+}
+
+/// Emits the event of a failed load
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn load_failed(err: &ConfigError) {
+    #[cfg(feature = "tracing")]
+    tracing::error!(
+        error.code = err.code(),
+        error.key_path = err.key_path(),
+        error.source = err.source_name(),
+        "{}",
+        err.message()
+    );
+}
@@ -0,0 +1,276 @@
+#[cfg(all(test, feature = "tokio", feature = "yaml"))]
+mod tests {
+    use crate::async_loading::{
+        load_config_with_param_async, load_config_with_sources_async, AsyncSource, CommandSource,
+        SourceFuture,
+    };
+    use crate::models::{ConfigError, EnvConfig, LoadingParam};
+    use crate::reload::ConfigHandle;
+    use crate::report::SourceKind;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value as JsonValue};
+    use std::env;
+    use std::fs;
+    use std::future::Future;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        host: String,
+        port: u16,
+        workers: u32,
+    }
+
+    struct StaticSource {
+        name: &'static str,
+        value: JsonValue,
+    }
+
+    impl AsyncSource for StaticSource {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn load(&self) -> SourceFuture<'_> {
+            Box::pin(async move { Ok(self.value.clone()) })
+        }
+    }
+
+    /// Records how many sources are loading at the same time
+    struct ConcurrentSource {
+        name: &'static str,
+        in_flight: Arc<AtomicUsize>,
+        max_in_flight: Arc<AtomicUsize>,
+    }
+
+    impl AsyncSource for ConcurrentSource {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn load(&self) -> SourceFuture<'_> {
+            Box::pin(async move {
+                let loading = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max_in_flight.fetch_max(loading, Ordering::SeqCst);
+                for _ in 0..10 {
+                    tokio::task::yield_now().await;
+                }
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(json!({ "workers": self.name.len() }))
+            })
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_load_config_with_param_async() {
+        info!("Starting test: test_load_config_with_param_async");
+        env::set_var("ASYNCPFX__HOST", "localhost");
+        env::set_var("ASYNCPFX__PORT", "8080");
+        env::set_var("ASYNCPFX__WORKERS", "2");
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("ASYNCPFX".to_string(), None)],
+            ..Default::default()
+        };
+
+        let result = block_on(load_config_with_param_async::<TestConfig>(&param));
+        for key in ["ASYNCPFX__HOST", "ASYNCPFX__PORT", "ASYNCPFX__WORKERS"] {
+            env::remove_var(key);
+        }
+
+        debug!("Result of load_config_with_param_async: {:?}", result);
+        assert_eq!(
+            result.unwrap(),
+            TestConfig {
+                host: "localhost".to_string(),
+                port: 8080,
+                workers: 2,
+            }
+        );
+        info!("Completed test: test_load_config_with_param_async successfully");
+    }
+
+    #[test]
+    fn test_async_sources_sit_between_file_and_env() {
+        info!("Starting test: test_async_sources_sit_between_file_and_env");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "host: file\nport: 1\nworkers: 1\n").unwrap();
+        env::set_var("ASYNCSRC__WORKERS", "4");
+        let param = LoadingParam {
+            file: Some(&path),
+            env_prefix: vec![EnvConfig::new("ASYNCSRC".to_string(), None)],
+            profile: Some("test"),
+            ..Default::default()
+        };
+        let remote = StaticSource {
+            name: "http://config/app",
+            value: json!({ "host": "remote", "port": 2, "workers": 2 }),
+        };
+        let defaults = StaticSource {
+            name: "defaults",
+            value: json!({ "port": 3 }),
+        };
+
+        let result = block_on(load_config_with_sources_async::<TestConfig>(
+            &param,
+            &[&remote, &defaults],
+        ));
+        env::remove_var("ASYNCSRC__WORKERS");
+
+        debug!("Result of load_config_with_sources_async: {:?}", result);
+        let (config, report) = result.unwrap();
+        assert_eq!(config.host, "remote");
+        assert_eq!(config.port, 2);
+        assert_eq!(config.workers, 4);
+        let sources: Vec<_> = report
+            .sources
+            .iter()
+            .map(|source| (source.name.as_str(), source.kind, source.keys))
+            .collect();
+        assert_eq!(
+            sources[1..3],
+            [
+                ("defaults", SourceKind::Async, 1),
+                ("http://config/app", SourceKind::Async, 3),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_value(&report).unwrap()["sources"][1]["kind"],
+            "async"
+        );
+        info!("Completed test: test_async_sources_sit_between_file_and_env successfully");
+    }
+
+    #[test]
+    fn test_failing_sources_are_reported() {
+        info!("Starting test: test_failing_sources_are_reported");
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("ASYNCFAIL".to_string(), None)],
+            ..Default::default()
+        };
+        let list = StaticSource {
+            name: "list",
+            value: json!([1, 2]),
+        };
+        let result = block_on(load_config_with_sources_async::<TestConfig>(
+            &param,
+            &[&list],
+        ));
+        debug!("Result with a source returning an array: {:?}", result);
+        assert!(matches!(
+            result,
+            Err(ConfigError::SourceFailed { ref name, .. }) if name == "list"
+        ));
+        let err = result.err().unwrap();
+        assert_eq!(err.code(), "dumbo_config::source_failed");
+        assert_eq!(err.source_name().as_deref(), Some("list"));
+        info!("Completed test: test_failing_sources_are_reported successfully");
+    }
+
+    #[test]
+    fn test_sources_load_concurrently_in_priority_order() {
+        info!("Starting test: test_sources_load_concurrently_in_priority_order");
+        env::set_var("ASYNCCONC__HOST", "localhost");
+        env::set_var("ASYNCCONC__PORT", "80");
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("ASYNCCONC".to_string(), None)],
+            ..Default::default()
+        };
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let source = |name| ConcurrentSource {
+            name,
+            in_flight: Arc::clone(&in_flight),
+            max_in_flight: Arc::clone(&max_in_flight),
+        };
+        let (high, low) = (source("high"), source("lowest"));
+
+        let result = block_on(load_config_with_sources_async::<TestConfig>(
+            &param,
+            &[&high, &low],
+        ));
+        env::remove_var("ASYNCCONC__HOST");
+        env::remove_var("ASYNCCONC__PORT");
+
+        debug!("Result of load_config_with_sources_async: {:?}", result);
+        let (config, report) = result.unwrap();
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(config.workers, 4);
+        let names: Vec<_> = report.sources.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["lowest", "high", "env:ASYNCCONC"]);
+        info!("Completed test: test_sources_load_concurrently_in_priority_order successfully");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_source() {
+        info!("Starting test: test_command_source");
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("ASYNCCMD".to_string(), None)],
+            ..Default::default()
+        };
+        let command = CommandSource::new(
+            "command",
+            "sh",
+            &["-c", r#"echo '{"host": "cmd", "port": 5, "workers": 6}'"#],
+        );
+        let result = block_on(load_config_with_sources_async::<TestConfig>(
+            &param,
+            &[&command],
+        ));
+        debug!("Result with a command source: {:?}", result);
+        assert_eq!(result.unwrap().0.host, "cmd");
+
+        let failing = CommandSource::new("failing", "sh", &["-c", "echo denied >&2; exit 3"]);
+        let result = block_on(load_config_with_sources_async::<TestConfig>(
+            &param,
+            &[&failing],
+        ));
+        debug!("Result with a failing command: {:?}", result);
+        match result {
+            Err(ConfigError::SourceFailed { name, reason, .. }) => {
+                assert_eq!(name, "failing");
+                assert!(reason.contains("denied"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        info!("Completed test: test_command_source successfully");
+    }
+
+    #[test]
+    fn test_change_stream() {
+        info!("Starting test: test_change_stream");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "host: a\nport: 1\nworkers: 1\n").unwrap();
+        let param = LoadingParam {
+            file: Some(&path),
+            profile: Some("test"),
+            ..Default::default()
+        };
+        let handle = ConfigHandle::<TestConfig>::builder(param).build().unwrap();
+        let mut changes = handle.subscribe_stream(&["workers"]);
+
+        fs::write(&path, "host: b\nport: 1\nworkers: 1\n").unwrap();
+        handle.reload().unwrap();
+        fs::write(&path, "host: b\nport: 1\nworkers: 8\n").unwrap();
+        handle.reload().unwrap();
+
+        let change = block_on(changes.next()).unwrap();
+        debug!("Change received by the stream: {:?}", change);
+        assert_eq!((change.old_version, change.new_version), (2, 3));
+        assert_eq!(change.changes[0].key_path, "workers");
+        assert_eq!(change.changes[0].new, Some(json!(8)));
+        info!("Completed test: test_change_stream successfully");
+    }
+}
@@ -1,11 +1,7 @@
 use crate::load_config;
-use log::{debug, error, info, warn};
+use log::{debug, info};
 use serde::{Deserialize, Serialize};
-use serde_yaml;
 use serial_test::serial;
-use std::env;
-use std::fs::File;
-use std::io::Read;
 use std::io::Write;
 use tempfile::NamedTempFile;
 
@@ -0,0 +1,213 @@
+#[cfg(all(test, feature = "yaml", feature = "toml"))]
+mod tests {
+    use crate::models::{ConfigError, EnvConfig, LoadingParam};
+    use crate::tests::helpers::create_config_file;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use std::env;
+    use tempfile::NamedTempFile;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        server: Server,
+        #[serde(default)]
+        replicas: Vec<Server>,
+    }
+
+    fn load(file: &NamedTempFile) -> Result<TestConfig, ConfigError> {
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("test"),
+            ..Default::default()
+        };
+        crate::loading::load_config_with_param::<TestConfig>(&param)
+    }
+
+    #[test]
+    fn test_parse_error_has_location_and_snippet() {
+        info!("Starting test: test_parse_error_has_location_and_snippet");
+        let file = create_config_file("server:\n  host: localhost\n  port: [8080\n", ".yaml");
+
+        let result = load(&file);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let err = result.unwrap_err();
+        assert!(matches!(err, ConfigError::Parse(_)));
+        let diagnostic = err.diagnostic().unwrap();
+        assert_eq!(diagnostic.path.as_deref(), Some(file.path()));
+        assert!(diagnostic.line.is_some());
+        assert!(diagnostic.snippet.as_ref().unwrap().contains('^'));
+        debug!("Rendered error:\n{}", err);
+        info!("Completed test: test_parse_error_has_location_and_snippet successfully");
+    }
+
+    #[test]
+    fn test_type_error_locates_key_in_file() {
+        info!("Starting test: test_type_error_locates_key_in_file");
+        let file = create_config_file(
+            "[server]\nhost = \"localhost\"\nport = \"eighty\"\n",
+            ".toml",
+        );
+
+        let result = load(&file);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let err = result.unwrap_err();
+        assert!(matches!(err, ConfigError::Deserialize(_)));
+        let diagnostic = err.diagnostic().unwrap();
+        assert_eq!(diagnostic.key_path.as_deref(), Some("server.port"));
+        assert_eq!(diagnostic.found.as_deref(), Some("string \"eighty\""));
+        assert!(diagnostic.expected.is_some());
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(3), Some(8)));
+        assert_eq!(
+            diagnostic.snippet.as_deref(),
+            Some("  |\n3 | port = \"eighty\"\n  |        ^")
+        );
+        debug!("Rendered error:\n{}", err);
+        info!("Completed test: test_type_error_locates_key_in_file successfully");
+    }
+
+    #[test]
+    fn test_type_error_in_array_item() {
+        info!("Starting test: test_type_error_in_array_item");
+        let file = create_config_file(
+            "server:\n  host: a\n  port: 1\nreplicas:\n  - host: b\n    port: x\n",
+            ".yaml",
+        );
+
+        let result = load(&file);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let err = result.unwrap_err();
+        let diagnostic = err.diagnostic().unwrap();
+        assert_eq!(diagnostic.key_path.as_deref(), Some("replicas[0].port"));
+        assert_eq!(diagnostic.line, Some(6));
+        info!("Completed test: test_type_error_in_array_item successfully");
+    }
+
+    #[test]
+    fn test_missing_key_is_reported() {
+        info!("Starting test: test_missing_key_is_reported");
+        let file = create_config_file("server:\n  host: a\n", ".yaml");
+
+        let result = load(&file);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.diagnostic().unwrap().key_path.as_deref(),
+            Some("server.port")
+        );
+        assert!(err
+            .to_string()
+            .starts_with("Missing configuration key 'server.port'."));
+        info!("Completed test: test_missing_key_is_reported successfully");
+    }
+
+    #[test]
+    fn test_key_of_sibling_block_is_not_located() {
+        info!("Starting test: test_key_of_sibling_block_is_not_located");
+        let yaml = create_config_file(
+            "server:\n  # port: 1\n  host: a\ncache:\n  host: b\n  port: 2\n",
+            ".yaml",
+        );
+        let toml = create_config_file(
+            "[server]\n# port = 1\nhost = \"a\"\n\n[cache]\nport = 2\n",
+            ".toml",
+        );
+
+        for file in [&yaml, &toml] {
+            let result = load(file);
+            debug!("Result of load_config_with_param: {:?}", result);
+            let err = result.unwrap_err();
+            let diagnostic = err.diagnostic().unwrap();
+            assert_eq!(diagnostic.key_path.as_deref(), Some("server.port"));
+            assert_eq!((diagnostic.line, diagnostic.column), (None, None));
+            assert!(diagnostic.snippet.is_none());
+        }
+
+        // A commented line declaring the key is skipped
+        let file = create_config_file("server:\n  # port: 1\n  host: a\n  port: x\n", ".yaml");
+        let line = load(&file).unwrap_err().diagnostic().unwrap().line;
+        assert_eq!(line, Some(4));
+        info!("Completed test: test_key_of_sibling_block_is_not_located successfully");
+    }
+
+    const PROFILE_DOCUMENTS: &str = "server:
+  host: a
+  port: 1
+---
+profile: staging
+server:
+  port: 2
+---
+profile: prod
+server:
+  port: x
+";
+
+    #[test]
+    fn test_type_error_located_in_profile_document() {
+        info!("Starting test: test_type_error_located_in_profile_document");
+        let file = create_config_file(PROFILE_DOCUMENTS, ".yaml");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("prod"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let err = result.unwrap_err();
+        let diagnostic = err.diagnostic().unwrap();
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(11), Some(9)));
+
+        // Without the profile, the value of the base document is located
+        let file = create_config_file(&PROFILE_DOCUMENTS.replace("port: 1", "port: y"), ".yaml");
+        let result = load(&file);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let line = result.unwrap_err().diagnostic().unwrap().line;
+        assert_eq!(line, Some(3));
+        info!("Completed test: test_type_error_located_in_profile_document successfully");
+    }
+
+    #[test]
+    fn test_type_error_names_env_variable() {
+        info!("Starting test: test_type_error_names_env_variable");
+        env::set_var("DIAGPFX__SERVER__HOST", "localhost");
+        env::set_var("DIAGPFX__SERVER__PORT", "eighty");
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("DIAGPFX".to_string(), None)],
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let err = result.unwrap_err();
+        let diagnostic = err.diagnostic().unwrap();
+        assert_eq!(diagnostic.origin.as_deref(), Some("DIAGPFX__SERVER__PORT"));
+        assert!(diagnostic.path.is_none());
+        assert!(err.to_string().contains("'DIAGPFX__SERVER__PORT'"));
+
+        env::remove_var("DIAGPFX__SERVER__HOST");
+        env::remove_var("DIAGPFX__SERVER__PORT");
+        info!("Completed test: test_type_error_names_env_variable successfully");
+    }
+
+    #[cfg(feature = "miette")]
+    #[test]
+    fn test_miette_labels_point_at_value() {
+        info!("Starting test: test_miette_labels_point_at_value");
+        use miette::Diagnostic as _;
+        let file = create_config_file("server:\n  host: a\n  port: x\n", ".yaml");
+
+        let err = load(&file).unwrap_err();
+        let labels: Vec<_> = err.labels().unwrap().collect();
+        debug!("Labels: {:?}", labels);
+        assert_eq!(labels[0].offset(), "server:\n  host: a\n  port: ".len());
+        assert!(err.source_code().is_some());
+        info!("Completed test: test_miette_labels_point_at_value successfully");
+    }
+}
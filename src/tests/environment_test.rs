@@ -0,0 +1,508 @@
+#[cfg(test)]
+mod tests {
+    use crate::environment::{replacement_name, similar_names, test_collect_values};
+    use crate::fields::KnownFields;
+    use crate::models::{ConfigError, EnvConfig, KeyMapping, LoadingParam};
+    use config::{Value, ValueKind};
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::env;
+    use std::sync::Arc;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        servers: Vec<Server>,
+        allowed_origins: Vec<String>,
+        limits: HashMap<String, u32>,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Node {
+        name: String,
+        children: Vec<Node>,
+        parent: Option<Box<Node>>,
+        owner: Owner,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Owner {
+        email: String,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TypedConfig {
+        zip: String,
+        name: String,
+        port: u16,
+        debug: bool,
+        ratio: f64,
+        retries: Option<u32>,
+        tags: Vec<String>,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Pool {
+        #[serde(rename = "maxConns")]
+        max_conns: u32,
+        #[serde(rename = "idle-timeout")]
+        idle_timeout: u32,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct RenamedConfig {
+        #[serde(rename = "dbPool")]
+        db_pool: Pool,
+        #[serde(rename = "databaseUrl")]
+        database_url: String,
+        replicas: Vec<Server>,
+        fallback: Option<Server>,
+    }
+
+    fn deserialize(env_config: &EnvConfig, vars: Vec<(&str, &str)>) -> TestConfig {
+        let values = test_collect_values(env_config, &KnownFields::default(), vars).unwrap();
+        debug!("Collected values: {:?}", values);
+        Value::new(None, ValueKind::Table(values))
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_indexed_arrays_delimited_lists_and_json() {
+        info!("Starting test: test_indexed_arrays_delimited_lists_and_json");
+        let env_config = EnvConfig::new("MY_APP".to_string(), None)
+            .with_indexed_arrays(true)
+            .with_list_separator(",", vec!["allowed_origins".to_string()])
+            .with_json_values(true);
+
+        let config = deserialize(
+            &env_config,
+            vec![
+                ("MY_APP__SERVERS__1__HOST", "backup"),
+                ("MY_APP__SERVERS__1__PORT", "8081"),
+                ("MY_APP__SERVERS__0__HOST", "primary"),
+                ("MY_APP__SERVERS__0__PORT", "8080"),
+                ("MY_APP__ALLOWED_ORIGINS", "a.example.com, b.example.com"),
+                ("MY_APP__LIMITS", r#"{"a": 1, "b": 2}"#),
+                ("OTHER_APP__LIMITS", "ignored"),
+            ],
+        );
+
+        assert_eq!(
+            config.servers,
+            vec![
+                Server {
+                    host: "primary".to_string(),
+                    port: 8080
+                },
+                Server {
+                    host: "backup".to_string(),
+                    port: 8081
+                },
+            ]
+        );
+        assert_eq!(
+            config.allowed_origins,
+            vec!["a.example.com", "b.example.com"]
+        );
+        assert_eq!(config.limits.get("a"), Some(&1));
+        assert_eq!(config.limits.get("b"), Some(&2));
+        info!("Completed test: test_indexed_arrays_delimited_lists_and_json successfully");
+    }
+
+    #[test]
+    fn test_options_are_disabled_by_default() {
+        info!("Starting test: test_options_are_disabled_by_default");
+        let env_config = EnvConfig::new("MY_APP".to_string(), None);
+        let values = test_collect_values(
+            &env_config,
+            &KnownFields::default(),
+            vec![
+                ("MY_APP__SERVERS__0__HOST", "primary"),
+                ("MY_APP__ALLOWED_ORIGINS", "a,b"),
+            ],
+        )
+        .unwrap();
+        debug!("Collected values: {:?}", values);
+
+        let servers = values.get("servers").unwrap().clone().into_table().unwrap();
+        assert!(servers.contains_key("0"));
+        let origins = values.get("allowed_origins").unwrap().clone();
+        assert_eq!(origins.into_string().unwrap(), "a,b");
+        info!("Completed test: test_options_are_disabled_by_default successfully");
+    }
+
+    #[test]
+    fn test_invalid_json_value() {
+        info!("Starting test: test_invalid_json_value");
+        let env_config = EnvConfig::new("MY_APP".to_string(), None).with_json_values(true);
+        let result = test_collect_values(
+            &env_config,
+            &KnownFields::default(),
+            vec![("MY_APP__LIMITS", "{not json")],
+        );
+        debug!("Result of test_collect_values: {:?}", result);
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidEnvValue { ref name, .. }) if name == "MY_APP__LIMITS"
+        ));
+        info!("Completed test: test_invalid_json_value successfully");
+    }
+
+    #[test]
+    fn test_load_config_with_env_arrays() {
+        info!("Starting test: test_load_config_with_env_arrays");
+        let env_vars = vec![
+            ("ENVARRAY__SERVERS__0__HOST", "primary"),
+            ("ENVARRAY__SERVERS__0__PORT", "8080"),
+            ("ENVARRAY__ALLOWED_ORIGINS", "a.example.com,b.example.com"),
+            ("ENVARRAY__LIMITS", r#"{"a": 1}"#),
+        ];
+        for (key, value) in &env_vars {
+            env::set_var(key, value);
+        }
+
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("ENVARRAY".to_string(), None)
+                .with_indexed_arrays(true)
+                .with_list_separator(",", vec!["allowed_origins".to_string()])
+                .with_json_values(true)],
+            ..Default::default()
+        };
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+
+        for (key, _) in env_vars {
+            env::remove_var(key);
+        }
+
+        debug!("Result of load_config_with_param: {:?}", result);
+        let config = result.unwrap();
+        assert_eq!(config.servers.len(), 1);
+        assert_eq!(config.allowed_origins.len(), 2);
+        assert_eq!(config.limits.get("a"), Some(&1));
+        info!("Completed test: test_load_config_with_env_arrays successfully");
+    }
+
+    #[test]
+    fn test_values_are_coerced_by_target_type() {
+        info!("Starting test: test_values_are_coerced_by_target_type");
+        let env_config = EnvConfig::new("MY_APP".to_string(), None)
+            .with_list_separator(",", vec!["tags".to_string()]);
+        let values = test_collect_values(
+            &env_config,
+            &KnownFields::default(),
+            vec![
+                ("MY_APP__ZIP", "01234"),
+                ("MY_APP__NAME", "true"),
+                ("MY_APP__PORT", "8080"),
+                ("MY_APP__DEBUG", "TRUE"),
+                ("MY_APP__RATIO", "0.5"),
+                ("MY_APP__RETRIES", "3"),
+                ("MY_APP__TAGS", "1,false"),
+            ],
+        )
+        .unwrap();
+
+        let config: TypedConfig = Value::new(None, ValueKind::Table(values))
+            .try_deserialize()
+            .unwrap();
+        debug!("Deserialized config: {:?}", config);
+        assert_eq!(
+            config,
+            TypedConfig {
+                zip: "01234".to_string(),
+                name: "true".to_string(),
+                port: 8080,
+                debug: true,
+                ratio: 0.5,
+                retries: Some(3),
+                tags: vec!["1".to_string(), "false".to_string()],
+            }
+        );
+        info!("Completed test: test_values_are_coerced_by_target_type successfully");
+    }
+
+    #[test]
+    fn test_invalid_number_reports_type_error() {
+        info!("Starting test: test_invalid_number_reports_type_error");
+        let env_config = EnvConfig::new("MY_APP".to_string(), None);
+        let values = test_collect_values(
+            &env_config,
+            &KnownFields::default(),
+            vec![("MY_APP__PORT", "eighty")],
+        )
+        .unwrap();
+
+        #[derive(Deserialize, Debug)]
+        struct PortConfig {
+            #[allow(dead_code)]
+            port: u16,
+        }
+        let result: Result<PortConfig, _> =
+            Value::new(None, ValueKind::Table(values)).try_deserialize();
+        debug!("Result of try_deserialize: {:?}", result);
+        assert!(result.is_err());
+        info!("Completed test: test_invalid_number_reports_type_error successfully");
+    }
+
+    #[test]
+    fn test_known_fields_probe() {
+        info!("Starting test: test_known_fields_probe");
+        let known = KnownFields::of::<RenamedConfig>();
+        debug!("Known fields: {:?}", known);
+        assert_eq!(
+            known.fields_at(""),
+            &["dbPool", "databaseUrl", "replicas", "fallback"]
+        );
+        assert_eq!(known.fields_at("dbPool"), &["maxConns", "idle-timeout"]);
+        assert_eq!(known.fields_at("replicas"), &["host", "port"]);
+        assert_eq!(known.fields_at("fallback"), &["host", "port"]);
+        assert!(known.paths().contains(&"dbPool.maxConns".to_string()));
+        info!("Completed test: test_known_fields_probe successfully");
+    }
+
+    #[test]
+    fn test_known_fields_probe_recursive_type() {
+        info!("Starting test: test_known_fields_probe_recursive_type");
+        let known = KnownFields::of::<Node>();
+        debug!("Known fields: {:?}", known);
+        assert_eq!(
+            known.fields_at(""),
+            &["name", "children", "parent", "owner"]
+        );
+        assert_eq!(
+            known.fields_at("children"),
+            &["name", "children", "parent", "owner"]
+        );
+        // Fields after a recursive one are still probed
+        assert_eq!(known.fields_at("owner"), &["email"]);
+        assert_eq!(known.fields_at("parent.owner"), &["email"]);
+        info!("Completed test: test_known_fields_probe_recursive_type successfully");
+    }
+
+    #[test]
+    fn test_key_mapping_strategies() {
+        info!("Starting test: test_key_mapping_strategies");
+        let known = ["maxConns", "idle-timeout"];
+        assert_eq!(
+            KeyMapping::Lowercase.map_segment("MAX_CONNS", &known),
+            "max_conns"
+        );
+        assert_eq!(
+            KeyMapping::Preserve.map_segment("MAX_CONNS", &known),
+            "MAX_CONNS"
+        );
+        assert_eq!(
+            KeyMapping::CamelCase.map_segment("MAX_CONNS", &known),
+            "maxConns"
+        );
+        assert_eq!(
+            KeyMapping::KebabCase.map_segment("IDLE_TIMEOUT", &known),
+            "idle-timeout"
+        );
+        assert_eq!(
+            KeyMapping::CaseInsensitive.map_segment("MAX_CONNS", &known),
+            "maxConns"
+        );
+        assert_eq!(
+            KeyMapping::CaseInsensitive.map_segment("IDLE_TIMEOUT", &known),
+            "idle-timeout"
+        );
+        assert_eq!(
+            KeyMapping::CaseInsensitive.map_segment("UNKNOWN", &known),
+            "unknown"
+        );
+        let custom = KeyMapping::Custom(Arc::new(|segment: &str| format!("x_{}", segment)));
+        assert_eq!(custom.map_segment("A", &known), "x_A");
+
+        assert_eq!(KeyMapping::CamelCase.unmap_key("maxConns"), "MAX_CONNS");
+        assert_eq!(
+            KeyMapping::KebabCase.unmap_key("idle-timeout"),
+            "IDLE_TIMEOUT"
+        );
+        assert_eq!(KeyMapping::Lowercase.unmap_key("max_conns"), "MAX_CONNS");
+        assert_eq!(KeyMapping::Preserve.unmap_key("MAX_CONNS"), "MAX_CONNS");
+        info!("Completed test: test_key_mapping_strategies successfully");
+    }
+
+    #[test]
+    fn test_case_insensitive_mapping_and_aliases() {
+        info!("Starting test: test_case_insensitive_mapping_and_aliases");
+        let env_config = EnvConfig::new("MY_APP".to_string(), None)
+            .with_key_mapping(KeyMapping::CaseInsensitive)
+            .with_indexed_arrays(true)
+            .with_alias("DATABASE_URL", "databaseUrl");
+        let values = test_collect_values(
+            &env_config,
+            &KnownFields::of::<RenamedConfig>(),
+            vec![
+                ("MY_APP__DB_POOL__MAX_CONNS", "20"),
+                ("MY_APP__DBPOOL__IDLE_TIMEOUT", "60"),
+                ("MY_APP__REPLICAS__0__HOST", "replica"),
+                ("MY_APP__REPLICAS__0__PORT", "5433"),
+                ("DATABASE_URL", "postgres://localhost/app"),
+            ],
+        )
+        .unwrap();
+        debug!("Collected values: {:?}", values);
+
+        let config: RenamedConfig = Value::new(None, ValueKind::Table(values))
+            .try_deserialize()
+            .unwrap();
+        assert_eq!(config.db_pool.max_conns, 20);
+        assert_eq!(config.db_pool.idle_timeout, 60);
+        assert_eq!(config.database_url, "postgres://localhost/app");
+        assert_eq!(config.replicas[0].host, "replica");
+        assert_eq!(config.fallback, None);
+        info!("Completed test: test_case_insensitive_mapping_and_aliases successfully");
+    }
+
+    #[test]
+    fn test_prefixed_variable_wins_over_alias() {
+        info!("Starting test: test_prefixed_variable_wins_over_alias");
+        let env_config = EnvConfig::new("MY_APP".to_string(), None)
+            .with_key_mapping(KeyMapping::CamelCase)
+            .with_alias("DATABASE_URL", "databaseUrl");
+        let values = test_collect_values(
+            &env_config,
+            &KnownFields::default(),
+            vec![
+                ("MY_APP__DATABASE_URL", "postgres://prefixed/app"),
+                ("DATABASE_URL", "postgres://alias/app"),
+            ],
+        )
+        .unwrap();
+        debug!("Collected values: {:?}", values);
+        let url = values.get("databaseUrl").unwrap().clone().into_string();
+        assert_eq!(url.unwrap(), "postgres://prefixed/app");
+        info!("Completed test: test_prefixed_variable_wins_over_alias successfully");
+    }
+
+    #[test]
+    fn test_replacement_name_for_deprecated_prefix() {
+        info!("Starting test: test_replacement_name_for_deprecated_prefix");
+        let current = EnvConfig::new("MY_APP".to_string(), None);
+        let legacy = EnvConfig::new("LEGACY".to_string(), Some("_".to_string()))
+            .deprecated()
+            .with_alias("OLD_DB_URL", "db.url");
+        assert_eq!(
+            replacement_name("LEGACY_DB_HOST", &legacy, &current),
+            Some("MY_APP__DB__HOST".to_string())
+        );
+        assert_eq!(
+            replacement_name("OLD_DB_URL", &legacy, &current),
+            Some("MY_APP__DB__URL".to_string())
+        );
+        assert_eq!(replacement_name("OTHER_DB_HOST", &legacy, &current), None);
+        info!("Completed test: test_replacement_name_for_deprecated_prefix successfully");
+    }
+
+    #[test]
+    fn test_newer_prefix_wins_over_deprecated_prefix() {
+        info!("Starting test: test_newer_prefix_wins_over_deprecated_prefix");
+        let env_vars = vec![
+            ("NEWPFX__HOST", "new-host"),
+            ("OLDPFX__HOST", "old-host"),
+            ("OLDPFX__PORT", "8081"),
+        ];
+        for (key, value) in &env_vars {
+            env::set_var(key, value);
+        }
+
+        let param = LoadingParam {
+            env_prefix: vec![
+                EnvConfig::new("NEWPFX".to_string(), None),
+                EnvConfig::new("OLDPFX".to_string(), None).deprecated(),
+            ],
+            ..Default::default()
+        };
+        let result = crate::loading::load_config_with_param::<Server>(&param);
+
+        for (key, _) in env_vars {
+            env::remove_var(key);
+        }
+
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(
+            result.unwrap(),
+            Server {
+                host: "new-host".to_string(),
+                port: 8081
+            }
+        );
+        info!("Completed test: test_newer_prefix_wins_over_deprecated_prefix successfully");
+    }
+
+    #[test]
+    fn test_similar_names() {
+        info!("Starting test: test_similar_names");
+        let names = vec![
+            "MYAPP__PORT".to_string(),
+            "my_app__host".to_string(),
+            "MY_APQ__DEBUG".to_string(),
+            "PATH".to_string(),
+            "OTHER_APP__PORT".to_string(),
+        ];
+
+        let similar = similar_names("MY_APP", "__", names);
+        debug!("Similar names: {:?}", similar);
+        assert_eq!(
+            similar,
+            vec!["my_app__host", "MYAPP__PORT", "MY_APQ__DEBUG"]
+        );
+        info!("Completed test: test_similar_names successfully");
+    }
+
+    #[test]
+    fn test_required_prefix_without_variables() {
+        info!("Starting test: test_required_prefix_without_variables");
+        env::set_var("REQPFXX__HOST", "localhost");
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("REQ_PFX".to_string(), None).required()],
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<Server>(&param);
+        env::remove_var("REQPFXX__HOST");
+
+        debug!("Result of load_config_with_param: {:?}", result);
+        match result {
+            Err(ConfigError::MissingEnvVars {
+                prefix, similar, ..
+            }) => {
+                assert_eq!(prefix, "REQ_PFX");
+                assert_eq!(similar, vec!["REQPFXX__HOST"]);
+            }
+            other => panic!("Expected MissingEnvVars, got {:?}", other),
+        }
+        info!("Completed test: test_required_prefix_without_variables successfully");
+    }
+
+    #[test]
+    fn test_prefix_matches_variables_of_any_case() {
+        info!("Starting test: test_prefix_matches_variables_of_any_case");
+        env::set_var("probe_app__maxConns", "20");
+        env::set_var("probe_app__idle-timeout", "60");
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("PROBE_APP".to_string(), None)
+                .with_key_mapping(KeyMapping::CaseInsensitive)],
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<Pool>(&param);
+        env::remove_var("probe_app__maxConns");
+        env::remove_var("probe_app__idle-timeout");
+
+        debug!("Result of load_config_with_param: {:?}", result);
+        let pool = result.unwrap();
+        assert_eq!(pool.max_conns, 20);
+        assert_eq!(pool.idle_timeout, 60);
+        info!("Completed test: test_prefix_matches_variables_of_any_case successfully");
+    }
+}
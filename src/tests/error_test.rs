@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use crate::i18n::Locale;
+    use crate::models::ConfigError;
+    #[cfg(feature = "yaml")]
+    use crate::models::LoadingParam;
+    use log::{debug, info};
+    #[cfg(feature = "yaml")]
+    use serde::{Deserialize, Serialize};
+    #[cfg(feature = "yaml")]
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    #[cfg(feature = "yaml")]
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        port: u16,
+    }
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        info!("Starting test: test_error_codes_are_stable");
+        let errors = vec![
+            (
+                ConfigError::FileNotFound {
+                    path: PathBuf::from("missing.yaml"),
+                    locale: Locale::En,
+                },
+                "dumbo_config::file_not_found",
+            ),
+            (
+                ConfigError::InvalidLoadingParam { locale: Locale::En },
+                "dumbo_config::invalid_loading_param",
+            ),
+            (
+                ConfigError::InvalidEnvConfig {
+                    prefix: "MY_APP".to_string(),
+                    separator: "_".to_string(),
+                    locale: Locale::En,
+                },
+                "dumbo_config::invalid_env_config",
+            ),
+            (
+                ConfigError::InvalidMergeStrategy {
+                    key: "servers".to_string(),
+                    value: "zip".to_string(),
+                    locale: Locale::En,
+                },
+                "dumbo_config::invalid_merge_strategy",
+            ),
+        ];
+
+        for (err, code) in errors {
+            debug!("Error {:?} has code {}", err, err.code());
+            assert_eq!(err.code(), code);
+        }
+        info!("Completed test: test_error_codes_are_stable successfully");
+    }
+
+    #[test]
+    fn test_error_details() {
+        info!("Starting test: test_error_details");
+        let err = ConfigError::InvalidMergeStrategy {
+            key: "servers".to_string(),
+            value: "zip".to_string(),
+            locale: Locale::En,
+        };
+        let details = err.details();
+        debug!("Details: {:?}", details);
+        assert_eq!(details.code, "dumbo_config::invalid_merge_strategy");
+        assert_eq!(details.key_path.as_deref(), Some("servers"));
+        assert_eq!(details.source, None);
+        assert!(details.hint.starts_with("Supported strategies are"));
+        assert_eq!(
+            err.to_string(),
+            format!("{}\n{}", details.message, details.hint)
+        );
+
+        let json = serde_json::to_value(&details).unwrap();
+        assert_eq!(json["code"], "dumbo_config::invalid_merge_strategy");
+        assert_eq!(json["key_path"], "servers");
+        info!("Completed test: test_error_details successfully");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_deserialize_error_details() {
+        info!("Starting test: test_deserialize_error_details");
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(b"port: eighty\n").unwrap();
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("test"),
+            ..Default::default()
+        };
+
+        let err = crate::loading::load_config_with_param::<TestConfig>(&param).unwrap_err();
+        let details = err.details();
+        debug!("Details: {:?}", details);
+        assert_eq!(details.code, "dumbo_config::deserialize");
+        assert_eq!(details.key_path.as_deref(), Some("port"));
+        assert_eq!(
+            details.source.as_deref(),
+            Some(&*file.path().display().to_string())
+        );
+        info!("Completed test: test_deserialize_error_details successfully");
+    }
+}
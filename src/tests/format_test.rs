@@ -0,0 +1,268 @@
+#[cfg(test)]
+mod tests {
+    #[cfg(all(feature = "json", feature = "toml", feature = "yaml", feature = "ini"))]
+    use crate::format::sniff_format;
+    use crate::format::Format;
+    use crate::models::{ConfigError, LoadingParam};
+    use crate::tests::helpers::create_config_file;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        server: Server,
+    }
+
+    const TOML_CONTENT: &str = r#"
+[server]
+host = "localhost"
+port = 8080
+"#;
+
+    #[cfg(all(feature = "json", feature = "toml", feature = "yaml", feature = "ini"))]
+    #[test]
+    fn test_sniff_format() {
+        info!("Starting test: test_sniff_format");
+        assert_eq!(
+            sniff_format(r#"{"server": {"host": "localhost", "port": 8080}}"#),
+            Some(Format::Json)
+        );
+        assert_eq!(sniff_format(TOML_CONTENT), Some(Format::Toml));
+        assert_eq!(
+            sniff_format("server:\n  host: localhost\n  port: 8080\n"),
+            Some(Format::Yaml)
+        );
+        assert_eq!(
+            sniff_format("[server]\nhost=localhost\nport=8080\n"),
+            Some(Format::Ini)
+        );
+        assert_eq!(sniff_format("just some words"), None);
+        assert_eq!(sniff_format(""), None);
+        info!("Completed test: test_sniff_format successfully");
+    }
+
+    // HOCON is tried first and reads such content too
+    #[cfg(all(feature = "properties", feature = "ini", not(feature = "hocon")))]
+    #[test]
+    fn test_sniff_properties_content() {
+        info!("Starting test: test_sniff_properties_content");
+        assert_eq!(
+            sniff_format("# server\nserver.host=localhost\nserver.port=8080\n"),
+            Some(Format::Properties)
+        );
+        assert_eq!(
+            sniff_format("[server]\nhost=localhost\nport=8080\n"),
+            Some(Format::Ini)
+        );
+        assert_eq!(sniff_format("just some words"), None);
+        info!("Completed test: test_sniff_properties_content successfully");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_load_conf_file_with_sniffed_toml() {
+        info!("Starting test: test_load_conf_file_with_sniffed_toml");
+        let file = create_config_file(TOML_CONTENT, ".conf");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(result.unwrap().server.port, 8080);
+        info!("Completed test: test_load_conf_file_with_sniffed_toml successfully");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_explicit_format_overrides_extension() {
+        info!("Starting test: test_explicit_format_overrides_extension");
+        let file = create_config_file(TOML_CONTENT, ".settings");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            file_format: Some(Format::Toml),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(result.unwrap().server.host, "localhost");
+        info!("Completed test: test_explicit_format_overrides_extension successfully");
+    }
+
+    #[test]
+    fn test_unknown_extension_is_an_error() {
+        info!("Starting test: test_unknown_extension_is_an_error");
+        let file = create_config_file(TOML_CONTENT, ".settings");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert!(matches!(
+            result,
+            Err(ConfigError::UnknownFileFormat { extension: Some(ref ext), .. }) if ext == "settings"
+        ));
+        info!("Completed test: test_unknown_extension_is_an_error successfully");
+    }
+
+    #[test]
+    fn test_unsniffable_content_is_an_error() {
+        info!("Starting test: test_unsniffable_content_is_an_error");
+        let file = create_config_file("just some words", ".conf");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert!(matches!(result, Err(ConfigError::UnknownFileFormat { .. })));
+        info!("Completed test: test_unsniffable_content_is_an_error successfully");
+    }
+
+    #[cfg(feature = "json5")]
+    #[test]
+    fn test_load_json5_file() {
+        info!("Starting test: test_load_json5_file");
+        let content = r#"{
+  // JSON5 allows comments, unquoted keys and trailing commas
+  server: { host: 'localhost', port: 8080, },
+}"#;
+        let file = create_config_file(content, ".json5");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(result.unwrap().server.port, 8080);
+
+        let legacy: Option<TestConfig> = crate::config::load_named_config(file.path());
+        debug!("Result of load_named_config: {:?}", legacy);
+        assert_eq!(legacy.unwrap().server.host, "localhost");
+        info!("Completed test: test_load_json5_file successfully");
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn test_load_ron_file() {
+        info!("Starting test: test_load_ron_file");
+        let content = r#"(
+    server: (host: "localhost", port: 8080),
+)"#;
+        let file = create_config_file(content, ".ron");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(result.unwrap().server.port, 8080);
+
+        let legacy: Option<TestConfig> = crate::config::load_named_config(file.path());
+        debug!("Result of load_named_config: {:?}", legacy);
+        assert_eq!(legacy.unwrap().server.host, "localhost");
+        info!("Completed test: test_load_ron_file successfully");
+    }
+
+    #[cfg(feature = "hocon")]
+    #[test]
+    fn test_load_hocon_file_with_include_and_substitutions() {
+        info!("Starting test: test_load_hocon_file_with_include_and_substitutions");
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("defaults.hocon"),
+            "defaults { host = localhost, port = 80 }\n",
+        )
+        .unwrap();
+        let path = dir.path().join("app.hocon");
+        std::fs::write(
+            &path,
+            r#"
+include "defaults.hocon"
+base_port = 8000
+server = ${defaults} { port = ${base_port} }
+server { port = 8080 }
+"#,
+        )
+        .unwrap();
+        let param = LoadingParam {
+            file: Some(&path),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(
+            result.unwrap().server,
+            Server {
+                host: "localhost".to_string(),
+                port: 8080
+            }
+        );
+        info!("Completed test: test_load_hocon_file_with_include_and_substitutions successfully");
+    }
+
+    #[cfg(feature = "hocon")]
+    #[test]
+    fn test_sniff_hocon_content() {
+        info!("Starting test: test_sniff_hocon_content");
+        assert_eq!(
+            sniff_format("server {\n  host = localhost\n  port = 8080\n}\n"),
+            Some(Format::Hocon)
+        );
+        info!("Completed test: test_sniff_hocon_content successfully");
+    }
+
+    #[cfg(feature = "hocon")]
+    #[test]
+    fn test_unresolved_hocon_substitution_is_an_error() {
+        info!("Starting test: test_unresolved_hocon_substitution_is_an_error");
+        let file = create_config_file("server { host = ${missing_host}, port = 8080 }", ".hocon");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert!(result.is_err());
+        info!("Completed test: test_unresolved_hocon_substitution_is_an_error successfully");
+    }
+
+    #[cfg(not(feature = "hocon"))]
+    #[test]
+    fn test_disabled_format_is_an_error() {
+        info!("Starting test: test_disabled_format_is_an_error");
+        let file = create_config_file("server { host = localhost, port = 8080 }", ".hocon");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::FormatNotEnabled {
+                format: Format::Hocon,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("'hocon' feature"));
+        info!("Completed test: test_disabled_format_is_an_error successfully");
+    }
+}
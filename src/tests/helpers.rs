@@ -0,0 +1,36 @@
+#[cfg(feature = "yaml")]
+use crate::models::LoadingParam;
+#[cfg(feature = "yaml")]
+use crate::reload::ConfigHandle;
+#[cfg(feature = "yaml")]
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+#[cfg(feature = "yaml")]
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+/// Writes the content to a temporary file with the given suffix, e.g. `.yaml`
+pub fn create_config_file(content: &str, suffix: &str) -> NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+    file.write_all(content.as_bytes()).unwrap();
+    file
+}
+
+/// Loading parameters reading the given file with the `test` profile
+#[cfg(feature = "yaml")]
+pub fn file_param(path: &Path) -> LoadingParam<'_> {
+    LoadingParam {
+        file: Some(path),
+        profile: Some("test"),
+        ..Default::default()
+    }
+}
+
+/// Builds a handle loading the given file, without a validator
+#[cfg(feature = "yaml")]
+pub fn handle<T>(path: &Path) -> ConfigHandle<T>
+where
+    T: for<'de> Deserialize<'de> + Serialize,
+{
+    ConfigHandle::builder(file_param(path)).build().unwrap()
+}
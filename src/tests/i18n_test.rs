@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod tests {
+    use crate::format::Format;
+    use crate::i18n::{Locale, Warning};
+    use crate::models::ConfigError;
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    use crate::models::{EnvConfig, LoadingParam};
+    use log::{debug, info};
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    use std::io::Write;
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        server: Server,
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    fn load_in(content: &str, suffix: &str, locale: Locale) -> String {
+        let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("test"),
+            locale: Some(locale),
+            ..Default::default()
+        };
+        let err = crate::loading::load_config_with_param::<TestConfig>(&param).unwrap_err();
+        assert_eq!(err.locale(), locale);
+        // Display uses the locale of the loading parameters
+        err.to_string()
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        info!("Starting test: test_parse_locale");
+        let cases = vec![
+            ("zh-CN", Some(Locale::ZhCn)),
+            ("zh_CN.UTF-8", Some(Locale::ZhCn)),
+            ("zh_CN.GB18030", Some(Locale::ZhCn)),
+            ("zh-Hans", Some(Locale::ZhCn)),
+            ("zh", Some(Locale::ZhCn)),
+            ("zh_TW.UTF-8", None),
+            ("en_US.UTF-8", Some(Locale::En)),
+            ("en", Some(Locale::En)),
+            ("C", Some(Locale::En)),
+            ("POSIX", Some(Locale::En)),
+            ("fr_FR.UTF-8", None),
+        ];
+
+        for (tag, expected) in cases {
+            debug!("Locale of {:?}: {:?}", tag, Locale::parse(tag));
+            assert_eq!(Locale::parse(tag), expected);
+        }
+        assert_eq!(Locale::ZhCn.to_string(), "zh-CN");
+        info!("Completed test: test_parse_locale successfully");
+    }
+
+    #[test]
+    fn test_locale_from_vars() {
+        info!("Starting test: test_locale_from_vars");
+        let from = |vars: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = vars
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            Locale::from_vars(|name| vars.get(name).cloned())
+        };
+
+        assert_eq!(from(&[]), Locale::En);
+        assert_eq!(from(&[("DUMBO_CONFIG_LANG", "zh_CN.UTF-8")]), Locale::ZhCn);
+        // The system locale is only followed when opted in
+        assert_eq!(from(&[("LANG", "zh_CN.UTF-8")]), Locale::En);
+        assert_eq!(
+            from(&[("LANG", "zh_CN.UTF-8"), ("DUMBO_CONFIG_LANG", "system")]),
+            Locale::ZhCn
+        );
+        assert_eq!(
+            from(&[
+                ("LANG", "en_US.UTF-8"),
+                ("LC_ALL", "zh_CN.UTF-8"),
+                ("DUMBO_CONFIG_LANG", "system")
+            ]),
+            Locale::ZhCn
+        );
+        assert_eq!(
+            from(&[
+                ("LANG", "zh_CN.UTF-8"),
+                ("LC_ALL", ""),
+                ("DUMBO_CONFIG_LANG", "system")
+            ]),
+            Locale::ZhCn
+        );
+        assert_eq!(
+            from(&[("LANG", "de_DE.UTF-8"), ("DUMBO_CONFIG_LANG", "system")]),
+            Locale::En
+        );
+        info!("Completed test: test_locale_from_vars successfully");
+    }
+
+    #[test]
+    fn test_every_error_is_translated() {
+        info!("Starting test: test_every_error_is_translated");
+        let errors = vec![
+            ConfigError::Config {
+                error: config::ConfigError::Frozen,
+                locale: Locale::En,
+            },
+            ConfigError::FileNotFound {
+                path: PathBuf::from("missing.yaml"),
+                locale: Locale::En,
+            },
+            ConfigError::ShowSettingsParseError {
+                value: "maybe".to_string(),
+                locale: Locale::En,
+            },
+            ConfigError::InvalidLoadingParam { locale: Locale::En },
+            ConfigError::InvalidEnvConfig {
+                prefix: "MY__APP".to_string(),
+                separator: "__".to_string(),
+                locale: Locale::En,
+            },
+            ConfigError::InvalidMergeStrategy {
+                key: "servers".to_string(),
+                value: "zip".to_string(),
+                locale: Locale::En,
+            },
+            ConfigError::InvalidEnvValue {
+                name: "MY_APP__LIMITS".to_string(),
+                reason: "invalid JSON".to_string(),
+                locale: Locale::En,
+            },
+            ConfigError::MissingEnvVars {
+                prefix: "MY_APP".to_string(),
+                similar: vec!["MYAPP__PORT".to_string()],
+                locale: Locale::En,
+            },
+            ConfigError::UnknownFileFormat {
+                path: PathBuf::from("app.xml"),
+                extension: Some("xml".to_string()),
+                locale: Locale::En,
+            },
+            ConfigError::FormatNotEnabled {
+                path: PathBuf::from("app.ron"),
+                format: Format::Ron,
+                locale: Locale::En,
+            },
+            ConfigError::LoadCancelled { locale: Locale::En },
+        ];
+
+        for err in errors {
+            let details = err.details_in(Locale::ZhCn);
+            debug!("Details of {:?} in zh-CN: {:?}", err, details);
+            assert_ne!(details.message, err.message_in(Locale::En));
+            assert_ne!(details.hint, err.hint_in(Locale::En));
+            assert_eq!(details.code, err.details_in(Locale::En).code);
+        }
+        info!("Completed test: test_every_error_is_translated successfully");
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    #[test]
+    fn test_diagnostics_are_translated() {
+        info!("Starting test: test_diagnostics_are_translated");
+        let missing = load_in("server:\n  host: a\n", ".yaml", Locale::ZhCn);
+        debug!("Missing key in zh-CN:\n{}", missing);
+        assert!(missing.starts_with("缺少配置键 'server.port'。"));
+
+        let invalid = load_in(
+            "[server]\nhost = \"localhost\"\nport = \"eighty\"\n",
+            ".toml",
+            Locale::ZhCn,
+        );
+        debug!("Invalid value in zh-CN:\n{}", invalid);
+        assert!(invalid.contains("第 3 行第 8 列 中键 'server.port' 的值无效"));
+        assert!(invalid.contains("3 | port = \"eighty\""));
+
+        let unparsable = load_in("server: [a\n", ".yaml", Locale::ZhCn);
+        debug!("Parse error in zh-CN:\n{}", unparsable);
+        assert!(unparsable.starts_with("解析 "));
+
+        let english = load_in("server:\n  host: a\n", ".yaml", Locale::En);
+        assert!(english.starts_with("Missing configuration key 'server.port'."));
+        info!("Completed test: test_diagnostics_are_translated successfully");
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    #[test]
+    fn test_every_load_error_uses_the_locale_of_the_parameters() {
+        info!("Starting test: test_every_load_error_uses_the_locale_of_the_parameters");
+        let params = vec![
+            LoadingParam {
+                locale: Some(Locale::ZhCn),
+                ..Default::default()
+            },
+            LoadingParam {
+                file: Some(Path::new("missing.yaml")),
+                locale: Some(Locale::ZhCn),
+                ..Default::default()
+            },
+            LoadingParam {
+                env_prefix: vec![EnvConfig::new("I18N_ABSENT".to_string(), None).required()],
+                locale: Some(Locale::ZhCn),
+                ..Default::default()
+            },
+        ];
+
+        for param in params {
+            let err = crate::loading::load_config_with_param::<TestConfig>(&param).unwrap_err();
+            debug!("Error in zh-CN: {}", err);
+            assert_eq!(err.locale(), Locale::ZhCn);
+            assert_eq!(err.to_string(), err.to_string_in(Locale::ZhCn));
+            assert_ne!(err.to_string(), err.to_string_in(Locale::En));
+        }
+        info!(
+            "Completed test: test_every_load_error_uses_the_locale_of_the_parameters successfully"
+        );
+    }
+
+    #[test]
+    fn test_warnings_are_translated() {
+        info!("Starting test: test_warnings_are_translated");
+        let warning = Warning::DeprecatedPrefix {
+            name: "LEGACY_APP__PORT",
+            prefix: "LEGACY_APP",
+            replacement: Some("MY_APP__PORT"),
+        };
+        debug!("Warning in zh-CN: {}", warning.text(Locale::ZhCn));
+        assert_eq!(
+            warning.text(Locale::ZhCn),
+            "环境变量 'LEGACY_APP__PORT' 使用了已弃用的前缀 'LEGACY_APP'，请改用 'MY_APP__PORT'。"
+        );
+        assert_eq!(
+            warning.text(Locale::En),
+            "Environment variable 'LEGACY_APP__PORT' uses deprecated prefix 'LEGACY_APP'. Please use 'MY_APP__PORT' instead."
+        );
+        info!("Completed test: test_warnings_are_translated successfully");
+    }
+}
@@ -1,10 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::models::{ConfigError, EnvConfig, LoadingParam};
-    use log::{debug, error, info, warn};
+    use log::{debug, info};
     use serde::{Deserialize, Serialize};
-    use std::path::Path;
 
     #[derive(Deserialize, Serialize, Debug, PartialEq)]
     struct TestConfig {
@@ -18,12 +16,16 @@ mod tests {
         info!("Starting test: test_invalid_loading_param_both_none");
         let param = LoadingParam {
             file: None,
-            env_prefix: None,
+            env_prefix: vec![],
+            ..Default::default()
         };
 
         let result = crate::loading::load_config_with_param::<TestConfig>(&param);
         debug!("Result of load_config_with_param: {:?}", result);
-        assert!(matches!(result, Err(ConfigError::InvalidLoadingParam)));
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidLoadingParam { .. })
+        ));
         info!("Completed test: test_invalid_loading_param_both_none successfully");
     }
 
@@ -32,10 +34,11 @@ mod tests {
         info!("Starting test: test_invalid_env_config_prefix_contains_separator");
         let param = LoadingParam {
             file: None,
-            env_prefix: Some(EnvConfig::new(
+            env_prefix: vec![EnvConfig::new(
                 "TEST_CONFIG".to_string(),
                 Some("_".to_string()),
-            )),
+            )],
+            ..Default::default()
         };
 
         let result = crate::loading::load_config_with_param::<TestConfig>(&param);
@@ -49,7 +52,8 @@ mod tests {
         info!("Starting test: test_valid_env_config");
         let param = LoadingParam {
             file: None,
-            env_prefix: Some(EnvConfig::new("TEST".to_string(), Some("_".to_string()))),
+            env_prefix: vec![EnvConfig::new("TEST".to_string(), Some("_".to_string()))],
+            ..Default::default()
         };
 
         // This should not return InvalidEnvConfig error
@@ -97,7 +101,8 @@ mod tests {
 
         let param = LoadingParam {
             file: None,
-            env_prefix: Some(EnvConfig::new(unique_prefix.to_string(), None)),
+            env_prefix: vec![EnvConfig::new(unique_prefix.to_string(), None)],
+            ..Default::default()
         };
 
         // This should not return an error, but rather load an empty configuration
@@ -106,10 +111,10 @@ mod tests {
         debug!("Result of load_config_with_param: {:?}", result);
 
         // The result should be an error, but not EnvPrefixNotFound
-        // It should be a config::ConfigError due to missing required fields
+        // It should be a deserialize error due to missing required fields
         match result {
-            Err(ConfigError::Config(_)) => {
-                // This is expected - config crate error due to missing fields
+            Err(ConfigError::Deserialize(_)) => {
+                // This is expected - deserialize error due to missing fields
                 info!("Got expected config error due to missing fields");
             }
             Err(e) => {
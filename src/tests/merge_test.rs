@@ -0,0 +1,191 @@
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use crate::merge::{self, Layer, MergeStrategies, MergeStrategy};
+    use crate::models::{ConfigError, LoadingParam};
+    use config::{Config, File, FileFormat, Source, Value, ValueKind};
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use std::io::Write;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        name: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        allowed_origins: Vec<String>,
+        servers: Vec<Server>,
+        timeout: Option<u32>,
+    }
+
+    fn yaml_layer(name: &str, content: &str) -> Layer {
+        let values = Config::builder()
+            .add_source(File::from_str(content, FileFormat::Yaml))
+            .build()
+            .unwrap()
+            .collect()
+            .unwrap();
+        Layer::new(name.to_string(), values)
+    }
+
+    fn merge(layers: Vec<Layer>, declared: &MergeStrategies) -> TestConfig {
+        let mut layers = layers;
+        let strategies = merge::collect_strategies(&mut layers, declared).unwrap();
+        let merged = merge::merge_layers(layers, &strategies);
+        Value::new(None, ValueKind::Table(merged))
+            .try_deserialize()
+            .unwrap()
+    }
+
+    const BASE: &str = r#"
+allowed_origins: ["a.example.com", "b.example.com"]
+servers:
+  - name: primary
+    port: 8080
+  - name: backup
+    port: 8081
+timeout: 30
+"#;
+
+    #[test]
+    fn test_parse_merge_strategy() {
+        info!("Starting test: test_parse_merge_strategy");
+        assert_eq!(
+            MergeStrategy::parse("replace"),
+            Some(MergeStrategy::Replace)
+        );
+        assert_eq!(MergeStrategy::parse("Append"), Some(MergeStrategy::Append));
+        assert_eq!(
+            MergeStrategy::parse(" prepend "),
+            Some(MergeStrategy::Prepend)
+        );
+        assert_eq!(
+            MergeStrategy::parse("merge_by_key:name"),
+            Some(MergeStrategy::MergeByKey("name".to_string()))
+        );
+        assert_eq!(MergeStrategy::parse("merge_by_key:"), None);
+        assert_eq!(MergeStrategy::parse("append:name"), None);
+        assert_eq!(MergeStrategy::parse("unknown"), None);
+        info!("Completed test: test_parse_merge_strategy successfully");
+    }
+
+    #[test]
+    fn test_arrays_are_replaced_by_default() {
+        info!("Starting test: test_arrays_are_replaced_by_default");
+        let profile = r#"
+allowed_origins: ["c.example.com"]
+"#;
+        let config = merge(
+            vec![yaml_layer("base", BASE), yaml_layer("profile", profile)],
+            &MergeStrategies::new(),
+        );
+        debug!("Merged config: {:?}", config);
+        assert_eq!(config.allowed_origins, vec!["c.example.com"]);
+        assert_eq!(config.servers.len(), 2);
+        info!("Completed test: test_arrays_are_replaced_by_default successfully");
+    }
+
+    #[test]
+    fn test_append_and_prepend_declared_in_file() {
+        info!("Starting test: test_append_and_prepend_declared_in_file");
+        let profile = r#"
+_merge:
+  allowed_origins: append
+  servers: prepend
+allowed_origins: ["c.example.com"]
+servers:
+  - name: canary
+    port: 9090
+"#;
+        let config = merge(
+            vec![yaml_layer("base", BASE), yaml_layer("profile", profile)],
+            &MergeStrategies::new(),
+        );
+        debug!("Merged config: {:?}", config);
+        assert_eq!(
+            config.allowed_origins,
+            vec!["a.example.com", "b.example.com", "c.example.com"]
+        );
+        let names: Vec<&str> = config.servers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["canary", "primary", "backup"]);
+        info!("Completed test: test_append_and_prepend_declared_in_file successfully");
+    }
+
+    #[test]
+    fn test_merge_by_key_declared_in_param() {
+        info!("Starting test: test_merge_by_key_declared_in_param");
+        let profile = r#"
+servers:
+  - name: backup
+    port: 9091
+  - name: canary
+    port: 9090
+"#;
+        let mut declared = MergeStrategies::new();
+        declared.insert(
+            "servers".to_string(),
+            MergeStrategy::MergeByKey("name".to_string()),
+        );
+        let config = merge(
+            vec![yaml_layer("base", BASE), yaml_layer("profile", profile)],
+            &declared,
+        );
+        debug!("Merged config: {:?}", config);
+        assert_eq!(
+            config.servers,
+            vec![
+                Server {
+                    name: "primary".to_string(),
+                    port: 8080
+                },
+                Server {
+                    name: "backup".to_string(),
+                    port: 9091
+                },
+                Server {
+                    name: "canary".to_string(),
+                    port: 9090
+                },
+            ]
+        );
+        info!("Completed test: test_merge_by_key_declared_in_param successfully");
+    }
+
+    #[test]
+    fn test_null_tombstone_deletes_key() {
+        info!("Starting test: test_null_tombstone_deletes_key");
+        let profile = r#"
+timeout: ~
+"#;
+        let config = merge(
+            vec![yaml_layer("base", BASE), yaml_layer("profile", profile)],
+            &MergeStrategies::new(),
+        );
+        debug!("Merged config: {:?}", config);
+        assert_eq!(config.timeout, None);
+        info!("Completed test: test_null_tombstone_deletes_key successfully");
+    }
+
+    #[test]
+    fn test_invalid_merge_strategy_in_file() {
+        info!("Starting test: test_invalid_merge_strategy_in_file");
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(b"_merge:\n  allowed_origins: shuffle\n")
+            .unwrap();
+
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidMergeStrategy { ref key, ref value, .. })
+                if key == "allowed_origins" && value == "shuffle"
+        ));
+        info!("Completed test: test_invalid_merge_strategy_in_file successfully");
+    }
+}
@@ -1,12 +1,41 @@
 #[cfg(test)]
+mod async_loading_test;
+#[cfg(all(test, feature = "yaml"))]
 mod config_test;
 #[cfg(test)]
+mod diagnostic_test;
+#[cfg(test)]
+mod environment_test;
+#[cfg(test)]
+mod error_test;
+#[cfg(test)]
+mod format_test;
+#[cfg(test)]
+mod helpers;
+#[cfg(test)]
+mod i18n_test;
+#[cfg(test)]
 mod loading_test;
 #[cfg(test)]
+mod merge_test;
+#[cfg(test)]
+mod properties_test;
+#[cfg(test)]
+mod reload_test;
+#[cfg(test)]
+mod report_test;
+#[cfg(test)]
 mod show_settings_test;
-
 #[cfg(test)]
-use log::{debug, error, info, warn};
+mod signal_test;
+#[cfg(test)]
+mod subscription_test;
+#[cfg(test)]
+mod tracing_test;
+#[cfg(test)]
+mod watch_test;
+#[cfg(test)]
+mod yaml_test;
 
 #[cfg(test)]
 #[ctor::ctor]
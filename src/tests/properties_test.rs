@@ -0,0 +1,173 @@
+#[cfg(all(test, feature = "properties"))]
+mod tests {
+    use crate::models::{ConfigError, LoadingParam};
+    use crate::properties::parse;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use std::io::Write;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Datasource {
+        url: String,
+        username: String,
+        #[serde(rename = "pool-size")]
+        pool_size: u32,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        datasource: Datasource,
+        servers: Vec<Server>,
+        greeting: String,
+        enabled: bool,
+    }
+
+    fn parse_value(content: &str, key: &str) -> String {
+        let values = parse(content, &"test.properties".to_string()).unwrap();
+        debug!("Parsed values: {:?}", values);
+        values.get(key).unwrap().clone().into_string().unwrap()
+    }
+
+    #[test]
+    fn test_parse_separators_and_comments() {
+        info!("Starting test: test_parse_separators_and_comments");
+        let content = "# comment\n! another comment\n\na=1\nb : 2\nc 3\n  d=  4\ne\n";
+        assert_eq!(parse_value(content, "a"), "1");
+        assert_eq!(parse_value(content, "b"), "2");
+        assert_eq!(parse_value(content, "c"), "3");
+        assert_eq!(parse_value(content, "d"), "4");
+        assert_eq!(parse_value(content, "e"), "");
+        let values = parse(content, &"test.properties".to_string()).unwrap();
+        assert_eq!(values.len(), 5);
+        info!("Completed test: test_parse_separators_and_comments successfully");
+    }
+
+    #[test]
+    fn test_parse_escapes_and_continuations() {
+        info!("Starting test: test_parse_escapes_and_continuations");
+        let content = "key\\ with\\=specials = tab\\there\nlong = first, \\\n       second, \\\n       third\nunicode = caf\\u00e9\npath = C:\\\\temp\n";
+        assert_eq!(parse_value(content, "key with=specials"), "tab\there");
+        assert_eq!(parse_value(content, "long"), "first, second, third");
+        assert_eq!(parse_value(content, "unicode"), "café");
+        assert_eq!(parse_value(content, "path"), "C:\\temp");
+        info!("Completed test: test_parse_escapes_and_continuations successfully");
+    }
+
+    #[test]
+    fn test_parse_invalid_unicode_escape() {
+        info!("Starting test: test_parse_invalid_unicode_escape");
+        let result = parse("a=1\nb=\\uZZ\n", &"test.properties".to_string());
+        debug!("Result of parse: {:?}", result);
+        assert_eq!(result.unwrap_err().line, 2);
+        info!("Completed test: test_parse_invalid_unicode_escape successfully");
+    }
+
+    #[test]
+    fn test_parse_surrogate_pairs() {
+        info!("Starting test: test_parse_surrogate_pairs");
+        assert_eq!(
+            parse_value("smile=\\uD83D\\uDE00!\n", "smile"),
+            "\u{1F600}!"
+        );
+
+        // A surrogate without its pair is rejected
+        for value in ["\\uD83D", "\\uD83Dx", "\\uD83D\\u0041", "\\uDE00"] {
+            let result = parse(
+                &format!("a=1\nb={}\n", value),
+                &"test.properties".to_string(),
+            );
+            debug!("Result of parse for {}: {:?}", value, result);
+            let err = result.unwrap_err();
+            assert_eq!(err.line, 2);
+            assert!(err.message.contains("unpaired surrogate"));
+        }
+        info!("Completed test: test_parse_surrogate_pairs successfully");
+    }
+
+    #[test]
+    fn test_parse_key_both_value_and_parent() {
+        info!("Starting test: test_parse_key_both_value_and_parent");
+        let content = "logging.level=INFO\nname=app\nlogging.level.root=DEBUG\n";
+        let result = parse(content, &"test.properties".to_string());
+        debug!("Result of parse: {:?}", result);
+        let err = result.unwrap_err();
+        assert_eq!(err.line, 3);
+        assert_eq!(
+            err.message,
+            "key 'logging.level' at line 1 is set as a value and as the parent of 'logging.level.root' at line 3"
+        );
+
+        let result = parse(
+            "servers[0].host=a\nservers=b\n",
+            &"test.properties".to_string(),
+        );
+        debug!("Result of parse: {:?}", result);
+        assert_eq!(result.unwrap_err().line, 2);
+        info!("Completed test: test_parse_key_both_value_and_parent successfully");
+    }
+
+    #[test]
+    fn test_load_properties_file() {
+        info!("Starting test: test_load_properties_file");
+        let content = r#"
+# Shared with the JVM services
+datasource.url=jdbc:postgresql://localhost/app
+datasource.username=app
+datasource.pool-size=10
+servers[0].host=primary
+servers[0].port=8080
+servers[1].host=backup
+servers[1].port=8081
+greeting=Hello, \
+    world
+enabled=true
+"#;
+        let mut file = tempfile::Builder::new()
+            .suffix(".properties")
+            .tempfile()
+            .unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let config = result.unwrap();
+        assert_eq!(config.datasource.url, "jdbc:postgresql://localhost/app");
+        assert_eq!(config.datasource.pool_size, 10);
+        assert_eq!(config.servers.len(), 2);
+        assert_eq!(config.servers[1].port, 8081);
+        assert_eq!(config.greeting, "Hello, world");
+        assert!(config.enabled);
+        info!("Completed test: test_load_properties_file successfully");
+    }
+
+    #[test]
+    fn test_load_invalid_properties_file() {
+        info!("Starting test: test_load_invalid_properties_file");
+        let mut file = tempfile::Builder::new()
+            .suffix(".properties")
+            .tempfile()
+            .unwrap();
+        file.write_all(b"a..b=1\n").unwrap();
+
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let diagnostic = result.as_ref().unwrap_err().diagnostic().unwrap();
+        assert!(matches!(result, Err(ConfigError::Parse(_))));
+        assert_eq!(diagnostic.line, Some(1));
+        info!("Completed test: test_load_invalid_properties_file successfully");
+    }
+}
@@ -0,0 +1,192 @@
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use crate::models::{ConfigError, LoadingParam};
+    use crate::reload::ConfigHandle;
+    use crate::tests::helpers::file_param;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        workers: u32,
+    }
+
+    fn handle(path: &Path, history_size: usize) -> ConfigHandle<TestConfig> {
+        ConfigHandle::builder(file_param(path))
+            .with_validator(|config: &TestConfig| match config.workers {
+                0 => Err("workers must be positive"),
+                _ => Ok(()),
+            })
+            .with_history_size(history_size)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reload_publishes_valid_config() {
+        info!("Starting test: test_reload_publishes_valid_config");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "workers: 2\n").unwrap();
+        let handle = handle(&path, 5);
+        assert_eq!(handle.current().version, 1);
+
+        fs::write(&path, "workers: 4\n").unwrap();
+        let result = handle.reload();
+        debug!("Result of reload: {:?}", result);
+        let snapshot = result.unwrap();
+        assert_eq!(snapshot.version, 2);
+        assert_eq!(handle.current().config.workers, 4);
+        assert_eq!(handle.current().report.sources.len(), 1);
+        assert!(handle.failures().is_empty());
+        info!("Completed test: test_reload_publishes_valid_config successfully");
+    }
+
+    #[test]
+    fn test_reload_keeps_previous_snapshot_on_failure() {
+        info!("Starting test: test_reload_keeps_previous_snapshot_on_failure");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "workers: 2\n").unwrap();
+        let handle = handle(&path, 5);
+
+        fs::write(&path, "workers: 0\n").unwrap();
+        let result = handle.reload();
+        debug!("Result of reload with invalid config: {:?}", result);
+        assert!(matches!(
+            result,
+            Err(ConfigError::ValidationFailed { reason, .. }) if reason == "workers must be positive"
+        ));
+
+        fs::write(&path, "workers: [\n").unwrap();
+        let result = handle.reload();
+        debug!("Result of reload with broken file: {:?}", result);
+        assert!(matches!(result, Err(ConfigError::Parse(_))));
+
+        assert_eq!(handle.current().version, 1);
+        assert_eq!(handle.current().config.workers, 2);
+        let failures = handle.failures();
+        debug!("Recorded failures: {:?}", failures);
+        let codes: Vec<_> = failures.iter().map(|failure| failure.code).collect();
+        assert_eq!(
+            codes,
+            vec!["dumbo_config::validation_failed", "dumbo_config::parse"]
+        );
+        assert!(failures.iter().all(|failure| failure.kept_version == 1));
+        assert!(failures[0].reason.contains("workers must be positive"));
+
+        // The next valid load gets the next version
+        fs::write(&path, "workers: 3\n").unwrap();
+        assert_eq!(handle.reload().unwrap().version, 2);
+        info!("Completed test: test_reload_keeps_previous_snapshot_on_failure successfully");
+    }
+
+    #[test]
+    fn test_concurrent_reloads_run_one_at_a_time() {
+        info!("Starting test: test_concurrent_reloads_run_one_at_a_time");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "workers: 2\n").unwrap();
+        let running = Arc::new(AtomicUsize::new(0));
+        let overlapped = Arc::new(AtomicUsize::new(0));
+        let (counter, overlaps) = (running.clone(), overlapped.clone());
+        let handle = Arc::new(
+            ConfigHandle::builder(file_param(&path))
+                .with_validator(move |_: &TestConfig| {
+                    if counter.fetch_add(1, Ordering::SeqCst) > 0 {
+                        overlaps.fetch_add(1, Ordering::SeqCst);
+                    }
+                    thread::sleep(Duration::from_millis(50));
+                    counter.fetch_sub(1, Ordering::SeqCst);
+                    Ok::<(), String>(())
+                })
+                .build()
+                .unwrap(),
+        );
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = Arc::clone(&handle);
+                thread::spawn(move || handle.reload().map(|snapshot| snapshot.version))
+            })
+            .collect();
+        let mut versions: Vec<u64> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap().unwrap())
+            .collect();
+        versions.sort();
+        debug!("Published versions: {:?}", versions);
+        assert_eq!(versions, vec![2, 3, 4, 5]);
+        assert_eq!(overlapped.load(Ordering::SeqCst), 0);
+        assert_eq!(handle.current().version, 5);
+        info!("Completed test: test_concurrent_reloads_run_one_at_a_time successfully");
+    }
+
+    #[test]
+    fn test_rollback_from_history() {
+        info!("Starting test: test_rollback_from_history");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "workers: 1\n").unwrap();
+        let handle = handle(&path, 3);
+        for workers in 2..=4 {
+            fs::write(&path, format!("workers: {}\n", workers)).unwrap();
+            handle.reload().unwrap();
+        }
+
+        let versions: Vec<_> = handle.history().iter().map(|s| s.version).collect();
+        debug!("Versions in history: {:?}", versions);
+        assert_eq!(versions, vec![2, 3, 4]);
+
+        let result = handle.rollback(1);
+        debug!("Result of rollback to an evicted version: {:?}", result);
+        assert!(matches!(
+            result,
+            Err(ConfigError::SnapshotNotFound { version: 1, .. })
+        ));
+        assert_eq!(handle.current().version, 4);
+
+        let snapshot = handle.rollback(2).unwrap();
+        assert_eq!(snapshot.config.workers, 2);
+        assert_eq!(handle.current().version, 2);
+        let versions: Vec<_> = handle.history().iter().map(|s| s.version).collect();
+        assert_eq!(versions, vec![3, 4, 2]);
+        info!("Completed test: test_rollback_from_history successfully");
+    }
+
+    #[test]
+    fn test_build_fails_on_invalid_initial_config() {
+        info!("Starting test: test_build_fails_on_invalid_initial_config");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "workers: 0\n").unwrap();
+        let param = LoadingParam {
+            file: Some(&path),
+            profile: Some("test"),
+            ..Default::default()
+        };
+        let result = ConfigHandle::builder(param)
+            .with_validator(|config: &TestConfig| {
+                if config.workers > 0 {
+                    Ok(())
+                } else {
+                    Err(format!("{} workers", config.workers))
+                }
+            })
+            .build();
+        debug!("Result of build: {:?}", result.as_ref().err());
+        let err = result.err().unwrap();
+        assert_eq!(err.code(), "dumbo_config::validation_failed");
+        assert_eq!(
+            err.message_in(crate::Locale::En),
+            "Configuration rejected by validation: 0 workers."
+        );
+        info!("Completed test: test_build_fails_on_invalid_initial_config successfully");
+    }
+}
@@ -0,0 +1,160 @@
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use crate::format::Format;
+    use crate::i18n::Locale;
+    use crate::models::{EnvConfig, LoadingParam};
+    use crate::report::SourceKind;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::env;
+    use std::io::Write;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        server: Server,
+        #[serde(default)]
+        replicas: Vec<Server>,
+        #[serde(default)]
+        labels: HashMap<String, String>,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Node {
+        name: String,
+        #[serde(default)]
+        children: Vec<Node>,
+    }
+
+    #[test]
+    fn test_report_lists_sources_and_warnings() {
+        info!("Starting test: test_report_lists_sources_and_warnings");
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(b"server:\n  host: localhost\n  port: 8080\n")
+            .unwrap();
+        env::set_var("RPTPFX__SERVER__PORT", "9090");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            env_prefix: vec![
+                EnvConfig::new("RPTPFX".to_string(), None),
+                EnvConfig::new("RPTMISSING".to_string(), None),
+            ],
+            profile: Some("test"),
+            locale: Some(Locale::En),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_report::<TestConfig>(&param);
+        env::remove_var("RPTPFX__SERVER__PORT");
+
+        debug!("Result of load_config_with_report: {:?}", result);
+        let (config, report) = result.unwrap();
+        assert_eq!(config.server.port, 9090);
+
+        let sources: Vec<_> = report
+            .sources
+            .iter()
+            .map(|source| (source.name.as_str(), source.kind, source.keys))
+            .collect();
+        let file_name = file.path().display().to_string();
+        assert_eq!(
+            sources,
+            vec![
+                (&*file_name, SourceKind::File, 2),
+                ("env:RPTMISSING", SourceKind::Env, 0),
+                ("env:RPTPFX", SourceKind::Env, 1),
+            ]
+        );
+        assert_eq!(report.sources[0].format, Some(Format::Yaml));
+
+        let codes: Vec<_> = report.warnings.iter().map(|warning| warning.code).collect();
+        assert!(codes.contains(&"dumbo_config::no_env_vars"));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|warning| warning.message.contains("'RPTMISSING'")));
+        assert!(report.ignored_keys.is_empty());
+
+        let json = serde_json::to_value(&report).unwrap();
+        debug!("Report as JSON: {}", json);
+        assert_eq!(json["sources"][1]["kind"], "env");
+        assert_eq!(json["sources"][0]["format"], "yaml");
+        info!("Completed test: test_report_lists_sources_and_warnings successfully");
+    }
+
+    #[test]
+    fn test_report_lists_ignored_keys() {
+        info!("Starting test: test_report_lists_ignored_keys");
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(
+            b"server:\n  host: a\n  port: 1\n  prot: 2\nreplicas:\n  - host: b\n    port: 3\n    weight: 1\nlabels:\n  team: ops\ntimeout: 30\n",
+        )
+        .unwrap();
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("test"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_report::<TestConfig>(&param);
+        debug!("Result of load_config_with_report: {:?}", result);
+        let (_, report) = result.unwrap();
+        assert_eq!(
+            report.ignored_keys,
+            vec!["replicas.weight", "server.prot", "timeout"]
+        );
+        info!("Completed test: test_report_lists_ignored_keys successfully");
+    }
+
+    #[test]
+    fn test_report_does_not_list_show_settings() {
+        info!("Starting test: test_report_does_not_list_show_settings");
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(b"server:\n  host: a\n  port: 1\n").unwrap();
+        env::set_var("RPTSHOW__SHOW_SETTINGS", "false");
+        env::set_var("RPTSHOW__TIMEOUT", "30");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            env_prefix: vec![EnvConfig::new("RPTSHOW".to_string(), None)],
+            profile: Some("test"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_report::<TestConfig>(&param);
+        env::remove_var("RPTSHOW__SHOW_SETTINGS");
+        env::remove_var("RPTSHOW__TIMEOUT");
+
+        debug!("Result of load_config_with_report: {:?}", result);
+        let (_, report) = result.unwrap();
+        assert_eq!(report.ignored_keys, vec!["timeout"]);
+        info!("Completed test: test_report_does_not_list_show_settings successfully");
+    }
+
+    #[test]
+    fn test_report_loads_recursive_type() {
+        info!("Starting test: test_report_loads_recursive_type");
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(
+            b"name: root\ncolour: red\nchildren:\n  - name: leaf\n    size: 1\n    children:\n      - name: deep\n",
+        )
+        .unwrap();
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("test"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_report::<Node>(&param);
+        debug!("Result of load_config_with_report: {:?}", result);
+        let (config, report) = result.unwrap();
+        assert_eq!(config.children[0].children[0].name, "deep");
+        assert_eq!(report.ignored_keys, vec!["children.size", "colour"]);
+        info!("Completed test: test_report_loads_recursive_type successfully");
+    }
+}
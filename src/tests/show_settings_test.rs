@@ -1,12 +1,12 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
     use crate::loading::test_should_show_settings;
-    use crate::models::{EnvConfig, LoadingParam};
+    use crate::models::{ConfigError, EnvConfig, KeyMapping, LoadingParam};
+    use crate::show_settings::{Provenance, SettingsDestination, SettingsFormat, ShowSettings};
     use log::{debug, info};
     use serde::{Deserialize, Serialize};
     use std::env;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
 
     #[derive(Deserialize, Serialize, Debug, PartialEq)]
     struct TestConfig {
@@ -25,17 +25,6 @@ mod tests {
         env::remove_var(key);
     }
 
-    // Helper function to ensure environment variable cleanup for multiple values
-    fn with_env_var_cleanup<F>(key: &str, test_fn: F)
-    where
-        F: FnOnce(),
-    {
-        test_fn();
-        if env::var_os(key).is_some() {
-            env::remove_var(key);
-        }
-    }
-
     #[test]
     fn test_should_show_settings_no_env_prefix() {
         info!("Starting test: test_should_show_settings_no_env_prefix");
@@ -43,13 +32,14 @@ mod tests {
         // Test with no env prefix (only file)
         let param = LoadingParam {
             file: Some(Path::new("dummy.yaml")),
-            env_prefix: None,
+            env_prefix: vec![],
+            ..Default::default()
         };
 
         // Even if SHOW_SETTINGS is true, should return false when no env_prefix
         with_env_var("TEST__SHOW_SETTINGS", "true", || {
             let result = test_should_show_settings(&param);
-            assert_eq!(result, false);
+            assert!(!result);
         });
 
         info!("Completed test: test_should_show_settings_no_env_prefix successfully");
@@ -61,7 +51,8 @@ mod tests {
 
         let param = LoadingParam {
             file: None,
-            env_prefix: Some(EnvConfig::new("TEST".to_string(), Some("__".to_string()))),
+            env_prefix: vec![EnvConfig::new("TEST".to_string(), Some("__".to_string()))],
+            ..Default::default()
         };
 
         // Test various truthy values for SHOW_SETTINGS
@@ -72,7 +63,7 @@ mod tests {
         for value in truthy_values {
             with_env_var("TEST__SHOW_SETTINGS", value, || {
                 let result = test_should_show_settings(&param);
-                assert_eq!(result, true, "Failed for SHOW_SETTINGS value: {}", value);
+                assert!(result, "Failed for SHOW_SETTINGS value: {}", value);
             });
         }
 
@@ -85,7 +76,8 @@ mod tests {
 
         let param = LoadingParam {
             file: None,
-            env_prefix: Some(EnvConfig::new("TEST".to_string(), Some("__".to_string()))),
+            env_prefix: vec![EnvConfig::new("TEST".to_string(), Some("__".to_string()))],
+            ..Default::default()
         };
 
         // Test falsy values for SHOW_SETTINGS
@@ -96,7 +88,7 @@ mod tests {
         for value in falsy_values {
             with_env_var("TEST__SHOW_SETTINGS", value, || {
                 let result = test_should_show_settings(&param);
-                assert_eq!(result, false, "Failed for SHOW_SETTINGS value: {}", value);
+                assert!(!result, "Failed for SHOW_SETTINGS value: {}", value);
             });
         }
 
@@ -106,7 +98,7 @@ mod tests {
             env::remove_var("TEST__SHOW_SETTINGS");
         }
         let result = test_should_show_settings(&param);
-        assert_eq!(result, false);
+        assert!(!result);
 
         info!("Completed test: test_should_show_settings_with_env_prefix_and_show_settings_false successfully");
     }
@@ -118,19 +110,20 @@ mod tests {
         // When both file and env_prefix are provided, env_prefix takes precedence
         let param = LoadingParam {
             file: Some(Path::new("dummy.yaml")),
-            env_prefix: Some(EnvConfig::new("TEST".to_string(), Some("__".to_string()))),
+            env_prefix: vec![EnvConfig::new("TEST".to_string(), Some("__".to_string()))],
+            ..Default::default()
         };
 
         // SHOW_SETTINGS=true should return true
         with_env_var("TEST__SHOW_SETTINGS", "true", || {
             let result = test_should_show_settings(&param);
-            assert_eq!(result, true);
+            assert!(result);
         });
 
         // SHOW_SETTINGS=false should return false
         with_env_var("TEST__SHOW_SETTINGS", "false", || {
             let result = test_should_show_settings(&param);
-            assert_eq!(result, false);
+            assert!(!result);
         });
 
         info!("Completed test: test_should_show_settings_both_file_and_env_prefix successfully");
@@ -155,7 +148,8 @@ mod tests {
 
         let param = LoadingParam {
             file: None,
-            env_prefix: Some(EnvConfig::new("TEST".to_string(), Some("__".to_string()))),
+            env_prefix: vec![EnvConfig::new("TEST".to_string(), Some("__".to_string()))],
+            ..Default::default()
         };
 
         // This should work and the should_show_settings logic should be exercised
@@ -169,4 +163,305 @@ mod tests {
 
         info!("Completed test: test_integration_show_settings_behavior successfully");
     }
+
+    #[test]
+    fn test_required_prefix_rejects_invalid_show_settings() {
+        info!("Starting test: test_required_prefix_rejects_invalid_show_settings");
+        let env_vars = vec![
+            ("STRICTSS__DATABASE_URL", "postgresql://localhost/test"),
+            ("STRICTSS__PORT", "5432"),
+            ("STRICTSS__DEBUG", "true"),
+            ("STRICTSS__SHOW_SETTINGS", "maybe"),
+        ];
+        for (key, value) in &env_vars {
+            env::set_var(key, value);
+        }
+
+        let lenient = LoadingParam {
+            env_prefix: vec![EnvConfig::new("STRICTSS".to_string(), None)],
+            ..Default::default()
+        };
+        let lenient_result = crate::loading::load_config_with_param::<TestConfig>(&lenient);
+
+        let strict = LoadingParam {
+            env_prefix: vec![EnvConfig::new("STRICTSS".to_string(), None).required()],
+            ..Default::default()
+        };
+        let strict_result = crate::loading::load_config_with_param::<TestConfig>(&strict);
+
+        for (key, _) in env_vars {
+            env::remove_var(key);
+        }
+
+        assert!(lenient_result.is_ok());
+        match strict_result {
+            Err(ConfigError::ShowSettingsParseError { value, .. }) => assert_eq!(value, "maybe"),
+            other => panic!("Expected ShowSettingsParseError, got {:?}", other),
+        }
+        info!("Completed test: test_required_prefix_rejects_invalid_show_settings successfully");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct NestedConfig {
+        name: String,
+        servers: Vec<Server>,
+        token: Option<String>,
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    fn nested_config() -> NestedConfig {
+        NestedConfig {
+            name: "my service".to_string(),
+            servers: vec![Server {
+                host: "localhost".to_string(),
+                port: 8080,
+            }],
+            token: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_show_settings_values() {
+        info!("Starting test: test_parse_show_settings_values");
+        let env_config = EnvConfig::new("MY_APP".to_string(), None);
+        let parse = |value: &str| ShowSettings::parse(value, &env_config);
+
+        assert!(parse("off").unwrap().is_none());
+        let settings = parse("TRUE").unwrap().unwrap();
+        assert_eq!(settings.format, SettingsFormat::Json);
+        assert_eq!(settings.destination, SettingsDestination::Log);
+
+        let settings = parse("yaml:stderr").unwrap().unwrap();
+        assert_eq!(settings.format, SettingsFormat::Yaml);
+        assert_eq!(settings.destination, SettingsDestination::Stderr);
+
+        let settings = parse("env:/tmp/app.env").unwrap().unwrap();
+        assert_eq!(settings.format, SettingsFormat::Env);
+        assert_eq!(
+            settings.destination,
+            SettingsDestination::File(PathBuf::from("/tmp/app.env"))
+        );
+
+        for value in ["maybe", "xml:stderr", "false:stderr"] {
+            let result = parse(value);
+            debug!("Result of parsing {:?}: {:?}", value, result);
+            assert!(matches!(
+                result,
+                Err(ConfigError::ShowSettingsParseError { value: v, .. }) if v == value
+            ));
+        }
+        info!("Completed test: test_parse_show_settings_values successfully");
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    #[test]
+    fn test_render_show_settings_formats() {
+        info!("Starting test: test_render_show_settings_formats");
+        let env_config = EnvConfig::new("MY_APP".to_string(), None);
+        let render = |value: &str| {
+            ShowSettings::parse(value, &env_config)
+                .unwrap()
+                .unwrap()
+                .render(&nested_config(), &Provenance::default())
+                .unwrap()
+        };
+
+        let env_output = render("env");
+        debug!("env output:\n{}", env_output);
+        assert_eq!(
+            env_output,
+            "MY_APP__NAME='my service'\n\
+             MY_APP__SERVERS__0__HOST=localhost\n\
+             MY_APP__SERVERS__0__PORT=8080"
+        );
+
+        let table_output = render("table");
+        debug!("table output:\n{}", table_output);
+        assert_eq!(
+            table_output,
+            "KEY              VALUE\n\
+             name             my service\n\
+             servers[0].host  localhost\n\
+             servers[0].port  8080"
+        );
+
+        let toml_output = render("toml");
+        debug!("toml output:\n{}", toml_output);
+        assert!(toml_output.contains("name = \"my service\""));
+        assert!(toml_output.contains("[[servers]]"));
+        assert!(!toml_output.contains("token"));
+
+        let yaml_output = render("yaml");
+        debug!("yaml output:\n{}", yaml_output);
+        assert!(yaml_output.contains("name: my service"));
+        assert!(yaml_output.contains("token: null"));
+        info!("Completed test: test_render_show_settings_formats successfully");
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct CamelConfig {
+        max_conns: u32,
+        db_pool: CamelPool,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct CamelPool {
+        idle_timeout: u64,
+    }
+
+    #[test]
+    fn test_env_format_round_trips_through_key_mapping() {
+        info!("Starting test: test_env_format_round_trips_through_key_mapping");
+        let config = CamelConfig {
+            max_conns: 20,
+            db_pool: CamelPool { idle_timeout: 60 },
+        };
+        for (prefix, key_mapping) in [
+            ("SSCAMEL", KeyMapping::CamelCase),
+            ("SSCASELESS", KeyMapping::CaseInsensitive),
+        ] {
+            let env_config = EnvConfig::new(prefix.to_string(), None).with_key_mapping(key_mapping);
+            let output = ShowSettings::parse("env", &env_config)
+                .unwrap()
+                .unwrap()
+                .render(&config, &Provenance::default())
+                .unwrap();
+            debug!("env output:\n{}", output);
+            assert_eq!(
+                output,
+                format!("{prefix}__DB_POOL__IDLE_TIMEOUT=60\n{prefix}__MAX_CONNS=20")
+            );
+
+            let vars: Vec<(&str, &str)> = output
+                .lines()
+                .map(|line| line.split_once('=').unwrap())
+                .collect();
+            for (name, value) in &vars {
+                env::set_var(name, value);
+            }
+            let param = LoadingParam {
+                env_prefix: vec![env_config],
+                ..Default::default()
+            };
+            let result = crate::loading::load_config_with_param::<CamelConfig>(&param);
+            for (name, _) in &vars {
+                env::remove_var(name);
+            }
+
+            debug!("Result of load_config_with_param: {:?}", result);
+            assert_eq!(result.unwrap(), config);
+        }
+        info!("Completed test: test_env_format_round_trips_through_key_mapping successfully");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_show_settings_writes_file() {
+        info!("Starting test: test_show_settings_writes_file");
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("effective.toml");
+        let env_vars = vec![
+            (
+                "SSFILE__DATABASE_URL",
+                "postgresql://localhost/test".to_string(),
+            ),
+            ("SSFILE__PORT", "5432".to_string()),
+            ("SSFILE__DEBUG", "true".to_string()),
+            (
+                "SSFILE__SHOW_SETTINGS",
+                format!("toml:{}", output.display()),
+            ),
+        ];
+        for (key, value) in &env_vars {
+            env::set_var(key, value);
+        }
+
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("SSFILE".to_string(), None)],
+            ..Default::default()
+        };
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+
+        for (key, _) in env_vars {
+            env::remove_var(key);
+        }
+
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert!(result.is_ok());
+        let written = std::fs::read_to_string(&output).unwrap();
+        debug!("Written settings:\n{}", written);
+        assert!(written.contains("port = 5432"));
+        assert!(written.contains("database_url = \"postgresql://localhost/test\""));
+        info!("Completed test: test_show_settings_writes_file successfully");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct DiffConfig {
+        server: Server,
+        #[serde(default)]
+        workers: u32,
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_show_settings_diff_lists_overridden_values() {
+        info!("Starting test: test_show_settings_diff_lists_overridden_values");
+        let dir = tempfile::tempdir().unwrap();
+        let config_file = dir.path().join("app.yaml");
+        std::fs::write(&config_file, "server:\n  host: localhost\n  port: 8080\n").unwrap();
+        let output = dir.path().join("diff.txt");
+        let env_vars = vec![
+            ("SSDIFF__SERVER__HOST", "localhost".to_string()),
+            ("SSDIFF__SERVER__PORT", "9090".to_string()),
+            ("SSDIFFOLD__SERVER__PORT", "9000".to_string()),
+            ("SSDIFFOLD__WORKERS", "4".to_string()),
+            (
+                "SSDIFF__SHOW_SETTINGS",
+                format!("diff:{}", output.display()),
+            ),
+        ];
+        for (key, value) in &env_vars {
+            env::set_var(key, value);
+        }
+
+        let param = LoadingParam {
+            file: Some(&config_file),
+            env_prefix: vec![
+                EnvConfig::new("SSDIFF".to_string(), None),
+                EnvConfig::new("SSDIFFOLD".to_string(), None),
+            ],
+            profile: Some("test"),
+            ..Default::default()
+        };
+        let result = crate::loading::load_config_with_param::<DiffConfig>(&param);
+
+        for (key, _) in env_vars {
+            env::remove_var(key);
+        }
+
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(result.unwrap().server.port, 9090);
+        let written = std::fs::read_to_string(&output).unwrap();
+        debug!("Written diff:\n{}", written);
+        let file_name = config_file.display();
+        assert_eq!(
+            written,
+            format!(
+                "server.port = 9090\n    {file_name} = 8080\n    env:SSDIFFOLD = 9000\n    env:SSDIFF = 9090  (wins)\n\
+                 workers = 4\n    {file_name} = (not set)\n    env:SSDIFFOLD = 4  (wins)\n"
+            )
+        );
+        info!("Completed test: test_show_settings_diff_lists_overridden_values successfully");
+    }
 }
@@ -0,0 +1,63 @@
+#[cfg(all(test, unix, feature = "signal", feature = "yaml"))]
+mod tests {
+    use crate::reload::ConfigHandle;
+    use crate::tests::helpers::file_param;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use signal_hook::consts::SIGHUP;
+    use std::fs;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        workers: u32,
+    }
+
+    // Raises SIGHUP until the condition holds, as the listener may not be waiting yet
+    fn raise_until(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            signal_hook::low_level::raise(SIGHUP).unwrap();
+            thread::sleep(Duration::from_millis(50));
+            if condition() {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn test_sighup_reloads_config() {
+        info!("Starting test: test_sighup_reloads_config");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "workers: 2\n").unwrap();
+        let handle = Arc::new(
+            ConfigHandle::builder(file_param(&path))
+                .with_validator(|config: &TestConfig| match config.workers {
+                    0 => Err("workers must be positive"),
+                    _ => Ok(()),
+                })
+                .build()
+                .unwrap(),
+        );
+        let listener = handle.reload_on_sighup().unwrap();
+
+        fs::write(&path, "workers: 4\n").unwrap();
+        assert!(raise_until(|| handle.current().config.workers == 4));
+        debug!("Version after SIGHUP: {}", handle.current().version);
+
+        // A failed reload keeps the previous configuration
+        fs::write(&path, "workers: 0\n").unwrap();
+        assert!(raise_until(|| !handle.failures().is_empty()));
+        let failures = handle.failures();
+        debug!("Recorded failures: {:?}", failures);
+        assert_eq!(failures[0].code, "dumbo_config::validation_failed");
+        assert_eq!(handle.current().config.workers, 4);
+
+        listener.stop();
+        info!("Completed test: test_sighup_reloads_config successfully");
+    }
+}
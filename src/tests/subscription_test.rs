@@ -0,0 +1,132 @@
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use crate::subscription::{matches, ConfigChange, KeyChange};
+    use crate::tests::helpers::handle;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use std::fs;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Http {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Db {
+        pool_size: u32,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        http: Http,
+        db: Db,
+    }
+
+    fn write_config(path: &Path, port: u16, pool_size: u32) {
+        fs::write(
+            path,
+            format!(
+                "http:\n  host: localhost\n  port: {}\ndb:\n  pool_size: {}\n",
+                port, pool_size
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_key_path_patterns() {
+        info!("Starting test: test_key_path_patterns");
+        assert!(matches("db.pool_size", "db.pool_size"));
+        assert!(!matches("db.pool_size", "db.pool_timeout"));
+        assert!(matches("http.*", "http.port"));
+        assert!(matches("http.*", "http.tls.cert"));
+        assert!(matches("http", "http.port"));
+        assert!(!matches("http.*", "http"));
+        assert!(!matches("http.*", "https.port"));
+        assert!(matches("servers.*.host", "servers[0].host"));
+        assert!(matches("servers[1]", "servers[1].port"));
+        assert!(!matches("servers[1]", "servers[0].port"));
+        info!("Completed test: test_key_path_patterns successfully");
+    }
+
+    #[test]
+    fn test_subscribers_receive_matching_changes() {
+        info!("Starting test: test_subscribers_receive_matching_changes");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        write_config(&path, 8080, 10);
+        let handle = handle::<TestConfig>(&path);
+
+        let received: Arc<Mutex<Vec<ConfigChange>>> = Arc::default();
+        let sink = received.clone();
+        let id = handle.subscribe(&["db.pool_size"], move |change| {
+            sink.lock().unwrap().push(change.clone());
+        });
+        let http_changes = handle.subscribe_channel(&["http.*"]);
+
+        // Only the pool size changes
+        write_config(&path, 8080, 20);
+        handle.reload().unwrap();
+        let changes = received.lock().unwrap().clone();
+        debug!("Changes received by the callback: {:?}", changes);
+        assert_eq!(
+            changes,
+            vec![ConfigChange {
+                old_version: 1,
+                new_version: 2,
+                changes: vec![KeyChange {
+                    key_path: "db.pool_size".to_string(),
+                    old: Some(json!(10)),
+                    new: Some(json!(20)),
+                }],
+            }]
+        );
+        assert!(http_changes.try_recv().is_err());
+
+        // Only the port changes
+        write_config(&path, 9090, 20);
+        handle.reload().unwrap();
+        let change = http_changes.try_recv().unwrap();
+        debug!("Change received by the channel: {:?}", change);
+        assert_eq!(change.changes.len(), 1);
+        assert_eq!(change.changes[0].key_path, "http.port");
+        assert_eq!(change.changes[0].new, Some(json!(9090)));
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        // Rolling back publishes the old values again
+        handle.rollback(1).unwrap();
+        let change = http_changes.try_recv().unwrap();
+        assert_eq!((change.old_version, change.new_version), (3, 1));
+        assert_eq!(change.changes[0].new, Some(json!(8080)));
+        assert_eq!(received.lock().unwrap()[1].changes[0].new, Some(json!(10)));
+
+        // Cancelled subscriptions are not notified
+        assert!(handle.unsubscribe(id));
+        assert!(!handle.unsubscribe(id));
+        write_config(&path, 8080, 30);
+        handle.reload().unwrap();
+        assert_eq!(received.lock().unwrap().len(), 2);
+        info!("Completed test: test_subscribers_receive_matching_changes successfully");
+    }
+
+    #[test]
+    fn test_failed_reload_does_not_notify() {
+        info!("Starting test: test_failed_reload_does_not_notify");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        write_config(&path, 8080, 10);
+        let handle = handle::<TestConfig>(&path);
+        let changes = handle.subscribe_channel(&["*"]);
+
+        fs::write(&path, "http: [\n").unwrap();
+        let result = handle.reload();
+        debug!("Result of reload: {:?}", result.as_ref().err());
+        assert!(result.is_err());
+        assert!(changes.try_recv().is_err());
+        info!("Completed test: test_failed_reload_does_not_notify successfully");
+    }
+}
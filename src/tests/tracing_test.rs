@@ -0,0 +1,311 @@
+#[cfg(all(test, feature = "tracing", feature = "yaml"))]
+mod tests {
+    use crate::models::{EnvConfig, LoadingParam};
+    use crate::tests::helpers::handle;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        server: Server,
+    }
+
+    #[derive(Debug, Clone)]
+    struct Recorded {
+        name: String,
+        parent: Option<u64>,
+        fields: Vec<(String, String)>,
+    }
+
+    impl Recorded {
+        fn field(&self, name: &str) -> Option<&str> {
+            self.fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+        }
+    }
+
+    struct Fields<'a>(&'a mut Vec<(String, String)>);
+
+    impl Visit for Fields<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push((field.name().to_string(), value.to_string()));
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+
+    #[derive(Default)]
+    struct Recordings {
+        next_id: AtomicU64,
+        stack: Mutex<Vec<u64>>,
+        spans: Mutex<Vec<(u64, Recorded)>>,
+        events: Mutex<Vec<Recorded>>,
+    }
+
+    /// Records spans with their parent, and events with the span they occur in
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Recordings>);
+
+    impl Recorder {
+        fn current(&self) -> Option<u64> {
+            self.0.stack.lock().unwrap().last().copied()
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let id = self.0.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut fields = Vec::new();
+            span.record(&mut Fields(&mut fields));
+            self.0.spans.lock().unwrap().push((
+                id,
+                Recorded {
+                    name: span.metadata().name().to_string(),
+                    parent: self.current(),
+                    fields,
+                },
+            ));
+            Id::from_u64(id)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Vec::new();
+            event.record(&mut Fields(&mut fields));
+            self.0.events.lock().unwrap().push(Recorded {
+                name: event.metadata().level().to_string(),
+                parent: self.current(),
+                fields,
+            });
+        }
+
+        fn enter(&self, span: &Id) {
+            self.0.stack.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _span: &Id) {
+            self.0.stack.lock().unwrap().pop();
+        }
+    }
+
+    #[test]
+    fn test_load_emits_spans_and_events() {
+        info!("Starting test: test_load_emits_spans_and_events");
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(b"server:\n  host: localhost\n  port: 8080\n")
+            .unwrap();
+        env::set_var("TRACEPFX__SERVER__PORT", "9090");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            env_prefix: vec![EnvConfig::new("TRACEPFX".to_string(), None)],
+            profile: Some("test"),
+            ..Default::default()
+        };
+
+        let recorder = Recorder::default();
+        let result = tracing::subscriber::with_default(recorder.clone(), || {
+            crate::loading::load_config_with_param::<TestConfig>(&param)
+        });
+        env::remove_var("TRACEPFX__SERVER__PORT");
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(result.unwrap().server.port, 9090);
+
+        let spans = recorder.0.spans.lock().unwrap().clone();
+        let events = recorder.0.events.lock().unwrap().clone();
+        debug!("Spans: {:?}", spans);
+        debug!("Events: {:?}", events);
+
+        let (load_id, load) = &spans[0];
+        assert_eq!(load.name, "dumbo_config.load");
+        assert_eq!(load.field("env_prefixes"), Some("TRACEPFX"));
+
+        let sources: Vec<_> = spans
+            .iter()
+            .filter(|(_, span)| span.name == "dumbo_config.source")
+            .collect();
+        assert_eq!(sources.len(), 2);
+        assert!(sources
+            .iter()
+            .all(|(_, span)| span.parent == Some(*load_id)));
+        assert_eq!(sources[0].1.field("source.kind"), Some("file"));
+        assert_eq!(sources[1].1.field("source.kind"), Some("env"));
+        assert_eq!(sources[1].1.field("source.prefix"), Some("TRACEPFX"));
+
+        let loaded: Vec<_> = events
+            .iter()
+            .filter(|event| event.field("message") == Some("configuration source loaded"))
+            .collect();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].parent, Some(sources[0].0));
+        assert_eq!(loaded[0].field("source.keys"), Some("2"));
+        assert_eq!(loaded[1].field("source.keys"), Some("1"));
+        assert_eq!(loaded[0].field("source.kind"), Some("file"));
+        assert_eq!(loaded[1].field("source.kind"), Some("env"));
+
+        let finished = events
+            .iter()
+            .find(|event| event.field("message") == Some("configuration loaded"))
+            .unwrap();
+        assert_eq!(finished.parent, Some(*load_id));
+        assert_eq!(finished.field("sources"), Some("2"));
+        info!("Completed test: test_load_emits_spans_and_events successfully");
+    }
+
+    #[test]
+    fn test_failed_load_emits_error_event() {
+        info!("Starting test: test_failed_load_emits_error_event");
+        let param = LoadingParam {
+            env_prefix: vec![EnvConfig::new("TRACEMISSING".to_string(), None).required()],
+            ..Default::default()
+        };
+
+        let recorder = Recorder::default();
+        let result = tracing::subscriber::with_default(recorder.clone(), || {
+            crate::loading::load_config_with_param::<TestConfig>(&param)
+        });
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert!(result.is_err());
+
+        let events = recorder.0.events.lock().unwrap().clone();
+        let error = events.iter().find(|event| event.name == "ERROR").unwrap();
+        assert_eq!(
+            error.field("error.code"),
+            Some("dumbo_config::missing_env_vars")
+        );
+        assert_eq!(error.field("error.source"), Some("TRACEMISSING"));
+        info!("Completed test: test_failed_load_emits_error_event successfully");
+    }
+
+    #[test]
+    fn test_warning_emits_one_event() {
+        info!("Starting test: test_warning_emits_one_event");
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(b"server:\n  host: localhost\n  port: 8080\n")
+            .unwrap();
+        let param = LoadingParam {
+            file: Some(file.path()),
+            env_prefix: vec![EnvConfig::new("TRACEWARN".to_string(), None)],
+            profile: Some("test"),
+            ..Default::default()
+        };
+
+        let recorder = Recorder::default();
+        let result = tracing::subscriber::with_default(recorder.clone(), || {
+            crate::loading::load_config_with_report::<TestConfig>(&param)
+        });
+        debug!("Result of load_config_with_report: {:?}", result);
+        let (_, report) = result.unwrap();
+
+        let events = recorder.0.events.lock().unwrap().clone();
+        let warnings: Vec<_> = events
+            .iter()
+            .filter(|event| event.field("warning.code").is_some())
+            .collect();
+        debug!("Warning events: {:?}", warnings);
+        assert_eq!(warnings.len(), report.warnings.len());
+        assert!(warnings
+            .iter()
+            .any(|event| event.field("warning.code") == Some("dumbo_config::no_env_vars")));
+        info!("Completed test: test_warning_emits_one_event successfully");
+    }
+
+    #[test]
+    fn test_failed_reload_warning_carries_error_fields() {
+        info!("Starting test: test_failed_reload_warning_carries_error_fields");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "server:\n  host: localhost\n  port: 8080\n").unwrap();
+        let handle = handle::<TestConfig>(&path);
+
+        fs::write(&path, "server:\n  host: localhost\n  port: eighty\n").unwrap();
+        let recorder = Recorder::default();
+        let result = tracing::subscriber::with_default(recorder.clone(), || handle.reload());
+        debug!("Result of reload: {:?}", result);
+        assert!(result.is_err());
+
+        let events = recorder.0.events.lock().unwrap().clone();
+        let warning = events
+            .iter()
+            .find(|event| event.field("warning.code") == Some("dumbo_config::reload_failed"))
+            .unwrap();
+        debug!("Reload warning: {:?}", warning);
+        assert_eq!(warning.name, "WARN");
+        assert_eq!(
+            warning.field("error.code"),
+            Some("dumbo_config::deserialize")
+        );
+        assert_eq!(warning.field("error.key_path"), Some("server.port"));
+        assert_eq!(
+            warning.field("error.source"),
+            Some(path.display().to_string().as_str())
+        );
+        info!("Completed test: test_failed_reload_warning_carries_error_fields successfully");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_load_runs_in_the_span_of_the_caller() {
+        info!("Starting test: test_async_load_runs_in_the_span_of_the_caller");
+        let mut file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        file.write_all(b"server:\n  host: localhost\n  port: 8080\n")
+            .unwrap();
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("test"),
+            ..Default::default()
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let recorder = Recorder::default();
+        let result = tracing::subscriber::with_default(recorder.clone(), || {
+            let _caller = tracing::info_span!("caller").entered();
+            runtime.block_on(crate::async_loading::load_config_with_param_async::<
+                TestConfig,
+            >(&param))
+        });
+        debug!("Result of load_config_with_param_async: {:?}", result);
+        assert!(result.is_ok());
+
+        // The blocking part of the load runs on another thread, in the span of the caller
+        let spans = recorder.0.spans.lock().unwrap().clone();
+        debug!("Spans: {:?}", spans);
+        let (caller_id, _) = spans
+            .iter()
+            .find(|(_, span)| span.name == "caller")
+            .unwrap();
+        let (_, load) = spans
+            .iter()
+            .find(|(_, span)| span.name == "dumbo_config.load")
+            .unwrap();
+        assert_eq!(load.parent, Some(*caller_id));
+        info!("Completed test: test_async_load_runs_in_the_span_of_the_caller successfully");
+    }
+}
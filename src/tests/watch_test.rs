@@ -0,0 +1,117 @@
+#[cfg(all(test, feature = "watch", feature = "yaml"))]
+mod tests {
+    use crate::reload::ConfigHandle;
+    use crate::tests::helpers::handle;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const DEBOUNCE: Duration = Duration::from_millis(100);
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        workers: u32,
+    }
+
+    fn shared_handle(path: &Path) -> Arc<ConfigHandle<TestConfig>> {
+        Arc::new(handle(path))
+    }
+
+    fn wait_for(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    // Waits long enough for any pending reload to have happened
+    fn settle() {
+        thread::sleep(DEBOUNCE * 5);
+    }
+
+    #[test]
+    fn test_partial_writes_reload_once() {
+        info!("Starting test: test_partial_writes_reload_once");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "workers: 1\n").unwrap();
+        let handle = shared_handle(&path);
+        let watcher = handle.watch_file(DEBOUNCE).unwrap();
+
+        fs::write(&path, "work").unwrap();
+        fs::write(&path, "workers: 2\n").unwrap();
+        assert!(wait_for(|| handle.current().config.workers == 2));
+        settle();
+        debug!("Versions in history: {}", handle.history().len());
+        assert_eq!(handle.current().version, 2);
+        assert!(handle.failures().is_empty());
+
+        // Writing the same content, or another file of the directory, does not reload
+        fs::write(&path, "workers: 2\n").unwrap();
+        fs::write(dir.path().join("other.yaml"), "workers: 3\n").unwrap();
+        settle();
+        assert_eq!(handle.current().version, 2);
+
+        watcher.stop();
+        info!("Completed test: test_partial_writes_reload_once successfully");
+    }
+
+    #[test]
+    fn test_rename_and_write_reloads() {
+        info!("Starting test: test_rename_and_write_reloads");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.yaml");
+        fs::write(&path, "workers: 1\n").unwrap();
+        let handle = shared_handle(&path);
+        let _watcher = handle.watch_file(DEBOUNCE).unwrap();
+
+        let copy = dir.path().join(".app.yaml.swp");
+        fs::write(&copy, "workers: 5\n").unwrap();
+        fs::rename(&copy, &path).unwrap();
+        assert!(wait_for(|| handle.current().config.workers == 5));
+        settle();
+        assert_eq!(handle.current().version, 2);
+        info!("Completed test: test_rename_and_write_reloads successfully");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_configmap_symlink_swap_reloads() {
+        info!("Starting test: test_configmap_symlink_swap_reloads");
+        use std::os::unix::fs::symlink;
+
+        // Layout of a mounted ConfigMap: app.yaml -> ..data/app.yaml, ..data -> ..v1
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("..v1")).unwrap();
+        fs::write(dir.path().join("..v1/app.yaml"), "workers: 1\n").unwrap();
+        symlink("..v1", dir.path().join("..data")).unwrap();
+        symlink("..data/app.yaml", dir.path().join("app.yaml")).unwrap();
+        let handle = shared_handle(&dir.path().join("app.yaml"));
+        let _watcher = handle.watch_file(DEBOUNCE).unwrap();
+
+        // The kubelet writes a new revision, then atomically retargets ..data
+        fs::create_dir(dir.path().join("..v2")).unwrap();
+        fs::write(dir.path().join("..v2/app.yaml"), "workers: 7\n").unwrap();
+        symlink("..v2", dir.path().join("..data_tmp")).unwrap();
+        fs::rename(dir.path().join("..data_tmp"), dir.path().join("..data")).unwrap();
+        fs::remove_dir_all(dir.path().join("..v1")).unwrap();
+
+        assert!(wait_for(|| handle.current().config.workers == 7));
+        settle();
+        debug!("Current version: {}", handle.current().version);
+        assert_eq!(handle.current().version, 2);
+
+        // Writes to the new revision are seen too
+        fs::write(dir.path().join("..v2/app.yaml"), "workers: 8\n").unwrap();
+        assert!(wait_for(|| handle.current().config.workers == 8));
+        info!("Completed test: test_configmap_symlink_swap_reloads successfully");
+    }
+}
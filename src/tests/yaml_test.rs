@@ -0,0 +1,249 @@
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use crate::models::LoadingParam;
+    use crate::tests::helpers::create_config_file;
+    use log::{debug, info};
+    use serde::{Deserialize, Serialize};
+    use serial_test::serial;
+    use std::env;
+    use std::fs;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct TestConfig {
+        name: String,
+        server: Server,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct ProfileConfig {
+        name: String,
+        profile: String,
+    }
+
+    const MULTI_DOCUMENT: &str = r#"
+name: service
+server:
+  host: localhost
+  port: 8080
+---
+profile: prod
+server:
+  host: prod.example.com
+---
+on-profile: [staging, qa]
+server:
+  host: staging.example.com
+  port: 9090
+"#;
+
+    #[test]
+    fn test_profile_documents_are_merged_over_base() {
+        info!("Starting test: test_profile_documents_are_merged_over_base");
+        let file = create_config_file(MULTI_DOCUMENT, ".yml");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("prod"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let config = result.unwrap();
+        assert_eq!(config.name, "service");
+        assert_eq!(
+            config.server,
+            Server {
+                host: "prod.example.com".to_string(),
+                port: 8080
+            }
+        );
+        info!("Completed test: test_profile_documents_are_merged_over_base successfully");
+    }
+
+    #[test]
+    fn test_profile_list_selects_document() {
+        info!("Starting test: test_profile_list_selects_document");
+        let file = create_config_file(MULTI_DOCUMENT, ".yml");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("qa"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(result.unwrap().server.port, 9090);
+        info!("Completed test: test_profile_list_selects_document successfully");
+    }
+
+    #[test]
+    #[serial]
+    fn test_without_profile_only_base_applies() {
+        info!("Starting test: test_without_profile_only_base_applies");
+        env::remove_var("ENV");
+        let file = create_config_file(MULTI_DOCUMENT, ".yml");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(result.unwrap().server.host, "localhost");
+        info!("Completed test: test_without_profile_only_base_applies successfully");
+    }
+
+    #[test]
+    fn test_single_document_keeps_profile_key() {
+        info!("Starting test: test_single_document_keeps_profile_key");
+        let file = create_config_file("name: service\nprofile: batch\n", ".yml");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("prod"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<ProfileConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        assert_eq!(result.unwrap().profile, "batch");
+        info!("Completed test: test_single_document_keeps_profile_key successfully");
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_selects_profile_from_env() {
+        info!("Starting test: test_load_config_selects_profile_from_env");
+        fs::write("config.yml", MULTI_DOCUMENT).unwrap();
+        env::set_var("ENV", "prod");
+
+        let config: Option<TestConfig> = crate::load_config();
+        debug!("Loaded config with profile: {:?}", config);
+        assert_eq!(config.unwrap().server.host, "prod.example.com");
+
+        env::set_var("ENV", "staging");
+        let config: Option<TestConfig> = crate::load_config();
+        debug!("Loaded config with profile: {:?}", config);
+        assert_eq!(config.unwrap().server.port, 9090);
+
+        fs::remove_file("config.yml").unwrap();
+        env::remove_var("ENV");
+        info!("Completed test: test_load_config_selects_profile_from_env successfully");
+    }
+
+    const MERGE_KEYS: &str = r#"
+defaults: &defaults
+  host: localhost
+  port: 8080
+name: service
+server:
+  <<: *defaults
+  port: 9090
+"#;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct MergeConfig {
+        name: String,
+        defaults: Server,
+        server: Server,
+    }
+
+    #[test]
+    fn test_merge_keys_are_applied() {
+        info!("Starting test: test_merge_keys_are_applied");
+        let file = create_config_file(MERGE_KEYS, ".yml");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("prod"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<MergeConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let expected = Server {
+            host: "localhost".to_string(),
+            port: 9090,
+        };
+        assert_eq!(result.unwrap().server, expected);
+
+        let legacy: Option<MergeConfig> = crate::load_named_config(file.path());
+        debug!("Result of load_named_config: {:?}", legacy);
+        assert_eq!(legacy.unwrap().server, expected);
+        info!("Completed test: test_merge_keys_are_applied successfully");
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct OriginsConfig {
+        allowed_origins: Vec<String>,
+    }
+
+    const APPEND_DOCUMENTS: &str = r#"
+allowed_origins: [a]
+---
+profile: prod
+_merge:
+  allowed_origins: append
+allowed_origins: [b]
+"#;
+
+    #[test]
+    #[serial]
+    fn test_merge_strategy_applies_across_documents() {
+        info!("Starting test: test_merge_strategy_applies_across_documents");
+        let file = create_config_file(APPEND_DOCUMENTS, ".yml");
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("prod"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<OriginsConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let expected = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(result.unwrap().allowed_origins, expected);
+
+        let (_, report) = crate::loading::load_config_with_report::<OriginsConfig>(&param).unwrap();
+        debug!("Ignored keys: {:?}", report.ignored_keys);
+        assert!(report
+            .ignored_keys
+            .iter()
+            .all(|key| !key.starts_with("_merge")));
+
+        env::set_var("ENV", "prod");
+        let legacy: Option<OriginsConfig> = crate::load_named_config(file.path());
+        env::remove_var("ENV");
+        debug!("Result of load_named_config: {:?}", legacy);
+        assert_eq!(legacy.unwrap().allowed_origins, expected);
+        info!("Completed test: test_merge_strategy_applies_across_documents successfully");
+    }
+
+    #[test]
+    fn test_duplicate_key_reports_line() {
+        info!("Starting test: test_duplicate_key_reports_line");
+        let file = create_config_file(
+            "name: service\nserver:\n  host: a\n  host: b\n  port: 1\n",
+            ".yml",
+        );
+        let param = LoadingParam {
+            file: Some(file.path()),
+            profile: Some("prod"),
+            ..Default::default()
+        };
+
+        let result = crate::loading::load_config_with_param::<TestConfig>(&param);
+        debug!("Result of load_config_with_param: {:?}", result);
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("duplicate entry with key \"host\""));
+        assert!(message.contains("line 3"));
+
+        let legacy: Option<TestConfig> = crate::load_named_config(file.path());
+        debug!("Result of load_named_config: {:?}", legacy);
+        assert!(legacy.is_none());
+        info!("Completed test: test_duplicate_key_reports_line successfully");
+    }
+}
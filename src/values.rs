@@ -0,0 +1,116 @@
+use config::{Config, Map, Source, Value, ValueKind};
+
+/// Source handing already parsed values to the config crate
+#[derive(Debug, Clone)]
+struct ParsedSource(Map<String, Value>);
+
+impl Source for ParsedSource {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, config::ConfigError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// Expands dotted (`server.port`) and indexed (`servers[0]`) keys into nested values
+pub(crate) fn expand_keys(
+    values: Map<String, Value>,
+) -> Result<Map<String, Value>, config::ConfigError> {
+    Config::builder()
+        .add_source(ParsedSource(values))
+        .build()?
+        .collect()
+}
+
+/// Inserts a value at the given path, creating intermediate tables as needed
+///
+/// A nested key wins over a scalar set at one of its parent keys.
+pub(crate) fn insert_path<S: AsRef<str>>(
+    table: &mut Map<String, Value>,
+    segments: &[S],
+    value: Value,
+) {
+    let (first, rest) = match segments.split_first() {
+        Some(parts) => parts,
+        None => return,
+    };
+
+    let first = first.as_ref();
+    if rest.is_empty() {
+        if !matches!(table.get(first), Some(existing) if matches!(existing.kind, ValueKind::Table(_)))
+        {
+            table.insert(first.to_string(), value);
+        }
+        return;
+    }
+
+    let entry = table
+        .entry(first.to_string())
+        .or_insert_with(|| Value::new(None, ValueKind::Table(Map::new())));
+    if !matches!(entry.kind, ValueKind::Table(_)) {
+        *entry = Value::new(None, ValueKind::Table(Map::new()));
+    }
+    if let ValueKind::Table(ref mut child) = entry.kind {
+        insert_path(child, rest, value);
+    }
+}
+
+/// Turns tables whose keys are all indices into arrays ordered by index
+pub(crate) fn indices_to_arrays(value: Value) -> Value {
+    let origin = value.origin().map(str::to_string);
+    match value.kind {
+        ValueKind::Table(table) => {
+            let table: Map<String, Value> = table
+                .into_iter()
+                .map(|(key, value)| (key, indices_to_arrays(value)))
+                .collect();
+
+            let all_indices =
+                !table.is_empty() && table.keys().all(|key| key.parse::<usize>().is_ok());
+            if !all_indices {
+                return Value::new(origin.as_ref(), ValueKind::Table(table));
+            }
+
+            let mut items: Vec<(usize, Value)> = table
+                .into_iter()
+                .map(|(key, value)| (key.parse::<usize>().unwrap_or_default(), value))
+                .collect();
+            items.sort_by_key(|(index, _)| *index);
+            let items: Vec<Value> = items.into_iter().map(|(_, value)| value).collect();
+            Value::new(origin.as_ref(), ValueKind::Array(items))
+        }
+        kind => Value::new(origin.as_ref(), kind),
+    }
+}
+
+/// Converts a JSON value into a configuration value
+pub(crate) fn json_to_value(json: serde_json::Value, origin: &String) -> Value {
+    let kind = match json {
+        serde_json::Value::Null => ValueKind::Nil,
+        serde_json::Value::Bool(b) => ValueKind::Boolean(b),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                ValueKind::I64(i)
+            } else if let Some(u) = n.as_u64() {
+                ValueKind::U64(u)
+            } else {
+                ValueKind::Float(n.as_f64().unwrap_or_default())
+            }
+        }
+        serde_json::Value::String(s) => ValueKind::String(s),
+        serde_json::Value::Array(items) => ValueKind::Array(
+            items
+                .into_iter()
+                .map(|item| json_to_value(item, origin))
+                .collect(),
+        ),
+        serde_json::Value::Object(map) => ValueKind::Table(
+            map.into_iter()
+                .map(|(key, value)| (key, json_to_value(value, origin)))
+                .collect(),
+        ),
+    };
+    Value::new(Some(origin), kind)
+}
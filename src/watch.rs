@@ -0,0 +1,180 @@
+use crate::reload::ConfigHandle;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Quiet period a `FileWatcher` waits for by default before reading the file
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Reloads a `ConfigHandle` when the content of its configuration file changes
+///
+/// Watching stops when the watcher is stopped or dropped.
+pub struct FileWatcher {
+    messages: Sender<Message>,
+    thread: Option<JoinHandle<()>>,
+}
+
+enum Message {
+    Event(notify::Result<Event>),
+    Stop,
+}
+
+/// Directories watched for a configuration file
+///
+/// The directory of the file sees symlink swaps such as the `..data` link of a mounted
+/// Kubernetes ConfigMap, and editors replacing the file by a renamed copy. The directory
+/// of the file the symlinks resolve to sees the file being written in place.
+struct WatchedDirs {
+    file: PathBuf,
+    dirs: Vec<PathBuf>,
+}
+
+impl<T> ConfigHandle<T>
+where
+    T: for<'de> Deserialize<'de> + Serialize + Send + Sync + 'static,
+{
+    /// Reloads the configuration when the content of the configuration file changes
+    ///
+    /// Events are debounced: the file is only read once no event came for `debounce`, and
+    /// reloaded only if the hash of its content differs from the one last read. Symlink
+    /// retargeting, rename-and-write and partial writes thus lead to one reload per change.
+    /// A reload of a partially written file that fails keeps the previous snapshot, like
+    /// `reload`, and the completed write is reloaded when it comes.
+    pub fn watch_file(self: &Arc<Self>, debounce: Duration) -> io::Result<FileWatcher> {
+        let file = self.param.file.clone().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the loading parameters have no configuration file to watch",
+            )
+        })?;
+
+        let (sender, messages) = mpsc::channel();
+        let events = sender.clone();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = events.send(Message::Event(event));
+        })
+        .map_err(io::Error::other)?;
+        let mut watched = WatchedDirs {
+            file: file.clone(),
+            dirs: Vec::new(),
+        };
+        watched.update(&mut watcher)?;
+
+        let handle = Arc::clone(self);
+        let mut last_hash = content_hash(&file);
+        let thread = thread::Builder::new()
+            .name("dumbo-config-watch".to_string())
+            .spawn(move || {
+                while wait_for_change(&messages, debounce) {
+                    if let Err(err) = watched.update(&mut watcher) {
+                        log::warn!("Failed to watch configuration file {:?}: {}", file, err);
+                    }
+                    let hash = content_hash(&file);
+                    if hash.is_none() || hash == last_hash {
+                        continue;
+                    }
+                    last_hash = hash;
+                    log::info!("Configuration file {:?} changed, reloading", file);
+                    // A failed reload is logged and recorded by the handle
+                    let _ = handle.reload();
+                }
+            })?;
+
+        Ok(FileWatcher {
+            messages: sender,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl FileWatcher {
+    /// Stops watching and waits for a reload in progress to finish
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        let _ = self.messages.send(Message::Stop);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl WatchedDirs {
+    /// Watches the directories of the file and of its resolved target, dropping stale ones
+    fn update(&mut self, watcher: &mut RecommendedWatcher) -> io::Result<()> {
+        let mut dirs = vec![parent(&self.file)];
+        if let Ok(target) = fs::canonicalize(&self.file) {
+            let target_dir = parent(&target);
+            if fs::canonicalize(&dirs[0]).ok().as_ref() != Some(&target_dir) {
+                dirs.push(target_dir);
+            }
+        }
+
+        for dir in &self.dirs {
+            if !dirs.contains(dir) {
+                // The directory may be gone already, e.g. an old ConfigMap revision
+                let _ = watcher.unwatch(dir);
+            }
+        }
+        for dir in &dirs {
+            if !self.dirs.contains(dir) {
+                watcher
+                    .watch(dir, RecursiveMode::NonRecursive)
+                    .map_err(io::Error::other)?;
+            }
+        }
+        self.dirs = dirs;
+        Ok(())
+    }
+}
+
+fn parent(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Waits for an event, then for `debounce` without events; returns false when stopped
+fn wait_for_change(messages: &Receiver<Message>, debounce: Duration) -> bool {
+    loop {
+        match messages.recv() {
+            Ok(Message::Event(Ok(_))) => break,
+            Ok(Message::Event(Err(err))) => {
+                log::warn!("Error while watching configuration file: {}", err)
+            }
+            Ok(Message::Stop) | Err(_) => return false,
+        }
+    }
+    loop {
+        match messages.recv_timeout(debounce) {
+            Ok(Message::Event(_)) => continue,
+            Err(RecvTimeoutError::Timeout) => return true,
+            Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => return false,
+        }
+    }
+}
+
+/// Hashes the content of the file, following symlinks; `None` while it cannot be read
+fn content_hash(file: &Path) -> Option<u64> {
+    let content = fs::read(file).ok()?;
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    Some(hasher.finish())
+}
@@ -0,0 +1,230 @@
+use crate::diagnostic::Step;
+use crate::format::Documents;
+use config::{Value, ValueKind};
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use serde_yaml::Value as YamlValue;
+use std::error::Error;
+use std::fmt;
+
+/// Keys tagging a YAML document with the profiles it applies to
+const PROFILE_KEYS: &[&str] = &["profile", "on-profile"];
+
+/// Parses the `---`-separated documents of the content, keeping those that apply
+///
+/// In a multi-document file, untagged documents always apply; documents tagged with a
+/// `profile:` or `on-profile:` key (a name, a comma-separated list or a sequence of
+/// names) only apply when the active profile is one of them. A single document keeps
+/// these keys as regular values. The selected documents are returned in order, each as
+/// its own table, and are merged like layers: later documents override earlier ones,
+/// following the `_merge` directives and null tombstones of any of them.
+///
+/// Anchors and aliases are resolved and `<<` merge keys are applied within each document.
+/// Duplicate keys in a mapping are reported as an error naming the key and its line.
+pub(crate) fn parse(
+    content: &str,
+    origin: &String,
+    profile: Option<&str>,
+) -> Result<Documents, Box<dyn Error + Send + Sync>> {
+    let documents = serde_yaml::Deserializer::from_str(content)
+        .map(YamlValue::deserialize)
+        .collect::<Result<Vec<_>, _>>()?;
+    let multi_document = documents.len() > 1;
+    let mut selected = Vec::new();
+
+    for (index, mut document) in documents.into_iter().enumerate() {
+        document.apply_merge()?;
+        if multi_document {
+            if let Some(profiles) = take_profiles(&mut document) {
+                if !applies(&profiles, profile) {
+                    log::debug!(
+                        "Skipping YAML document {} for profiles {:?}; active profile: {:?}",
+                        index,
+                        profiles,
+                        profile
+                    );
+                    continue;
+                }
+            }
+        }
+        match to_value(document, origin).kind {
+            ValueKind::Table(table) => selected.push(table),
+            ValueKind::Nil => {}
+            _ => return Err("the root of a YAML document must be a mapping".into()),
+        }
+    }
+
+    Ok(selected)
+}
+
+/// Finds the 1-based line and column of the value at the key path
+///
+/// The documents are walked with the YAML parser, so comments, flow collections and
+/// sibling mappings are told apart. In a multi-document file the value comes from the
+/// last selected document that sets it; keys inherited through `<<` are not located.
+pub(crate) fn locate(
+    content: &str,
+    steps: &[Step],
+    profile: Option<&str>,
+) -> Option<(usize, usize)> {
+    let documents: Vec<YamlValue> = serde_yaml::Deserializer::from_str(content)
+        .map(YamlValue::deserialize)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let multi_document = documents.len() > 1;
+    let selected: Vec<bool> = documents
+        .into_iter()
+        .map(|mut document| match take_profiles(&mut document) {
+            Some(profiles) if multi_document => applies(&profiles, profile),
+            _ => true,
+        })
+        .collect();
+
+    let documents: Vec<_> = serde_yaml::Deserializer::from_str(content).collect();
+    documents
+        .into_iter()
+        .zip(selected)
+        .rev()
+        .filter(|(_, selected)| *selected)
+        .find_map(|(document, _)| {
+            let err = Walk(steps).deserialize(document).err()?;
+            let location = err.location().filter(|_| err.to_string().contains(FOUND))?;
+            Some((location.line(), location.column()))
+        })
+}
+
+/// Expected by the visitor of the located value, so that its error can be told apart
+const FOUND: &str = "the located value";
+
+/// Follows the key path through a document
+///
+/// The value at the end of the path is refused, which makes the parser report the
+/// position where it starts.
+struct Walk<'a>(&'a [Step]);
+
+impl<'de> DeserializeSeed<'de> for Walk<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        match self.0 {
+            [] => deserializer.deserialize_any(Found),
+            _ => deserializer.deserialize_any(self),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for Walk<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a mapping or a sequence")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let Some((Step::Key(name), rest)) = self.0.split_first() else {
+            return Err(de::Error::custom("not a sequence"));
+        };
+        while let Some(key) = map.next_key::<YamlValue>()? {
+            if scalar_to_string(&key).as_ref() == Some(name) {
+                return map.next_value_seed(Walk(rest));
+            }
+            map.next_value::<IgnoredAny>()?;
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let Some((Step::Index(index), rest)) = self.0.split_first() else {
+            return Err(de::Error::custom("not a mapping"));
+        };
+        for _ in 0..*index {
+            if seq.next_element::<IgnoredAny>()?.is_none() {
+                return Ok(());
+            }
+        }
+        seq.next_element_seed(Walk(rest))?;
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(())
+    }
+}
+
+/// Refuses any value, see `Walk`
+struct Found;
+
+impl Visitor<'_> for Found {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(FOUND)
+    }
+}
+
+/// Tells whether a document tagged with the profiles applies to the active profile
+fn applies(profiles: &[String], profile: Option<&str>) -> bool {
+    profile.is_some_and(|active| profiles.iter().any(|p| p.eq_ignore_ascii_case(active)))
+}
+
+/// Removes the profile key from a mapping document, returning the profiles it names
+fn take_profiles(document: &mut YamlValue) -> Option<Vec<String>> {
+    let YamlValue::Mapping(mapping) = document else {
+        return None;
+    };
+    let tag = PROFILE_KEYS.iter().find_map(|key| mapping.remove(*key))?;
+    let profiles = match tag {
+        YamlValue::Sequence(items) => items.iter().filter_map(scalar_to_string).collect(),
+        other => scalar_to_string(&other)
+            .map(|names| {
+                names
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+    Some(profiles)
+}
+
+/// Converts a YAML value into a configuration value
+fn to_value(value: YamlValue, origin: &String) -> Value {
+    let kind = match value {
+        YamlValue::Null => ValueKind::Nil,
+        YamlValue::Bool(b) => ValueKind::Boolean(b),
+        YamlValue::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                ValueKind::I64(i)
+            } else if let Some(u) = n.as_u64() {
+                ValueKind::U64(u)
+            } else {
+                ValueKind::Float(n.as_f64().unwrap_or_default())
+            }
+        }
+        YamlValue::String(s) => ValueKind::String(s),
+        YamlValue::Sequence(items) => ValueKind::Array(
+            items
+                .into_iter()
+                .map(|item| to_value(item, origin))
+                .collect(),
+        ),
+        YamlValue::Mapping(mapping) => ValueKind::Table(
+            mapping
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = scalar_to_string(&key).unwrap_or_default();
+                    (key, to_value(value, origin))
+                })
+                .collect(),
+        ),
+        YamlValue::Tagged(tagged) => return to_value(tagged.value, origin),
+    };
+    Value::new(Some(origin), kind)
+}
+
+/// Gets the text of a scalar, e.g. a mapping key
+fn scalar_to_string(value: &YamlValue) -> Option<String> {
+    match value {
+        YamlValue::String(s) => Some(s.clone()),
+        YamlValue::Number(n) => Some(n.to_string()),
+        YamlValue::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}